@@ -17,58 +17,44 @@ use core::cell::UnsafeCell;
 use core::panic::PanicInfo;
 
 use buttons::DEBOUNCED_BUTTONS_CONTEXT;
-use equations::{
-    check_roots_in_range, ChordSolver, Equations, Logarithm, NonLinearEquation, Pow, SecantSolver,
-    SimpleIterationSolver, Solver, SolverInput, Trigonometry, LEFT_BORDER, POINT_AMOUNT,
-    POINT_INTERVAL_LENGTH, RIGHT_BORDER,
-};
 use interrupts::without_interrupts;
 use lazy::Lazy;
-use protocol::point::{Point, PointCoordinate};
-use protocol::request::compute_method::Method;
-use protocol::request::payloads::ComputeRootPayload;
-use protocol::request::SingleEquation;
-use protocol::response::InitialApproximationsResponse;
-use protocol::TNumber;
+use protocol::device_info::DeviceInfo;
+use protocol::expression::ProgramAssembler;
+use protocol::notification::Notification;
+use protocol::request::compute_method::{Method, SystemMethod};
+use protocol::request::payloads::{ComputeRootPayload, UploadChunk};
+use protocol::response::{ComputeRootResponse, InitialApproximationsResponse, MethodError};
+use protocol::PROTOCOL_VERSION;
 use protocol_handler::Connection;
 use ruduino::cores::current::port;
 use ruduino::Pin;
-use system_of_equations::{EquationWithPhi, SimpleIteratorSolverForSystems, SystemOfEquations};
+use solvers::system_of_equations::SystemOfEquations;
+use solvers::{
+    builtin, for_each_point, is_bracket_valid, Equations, NonLinearEquation, Tracer, POINT_AMOUNT,
+};
 
 mod buttons;
-mod equations;
 mod interrupts;
 mod lazy;
 mod protocol_handler;
 mod ring_buffer;
-mod system_of_equations;
 mod usart;
+mod user_equation;
 
-const SINGLE: [NonLinearEquation; 2] = [
+const SINGLE: [NonLinearEquation; 3] = [
+    builtin::SQUARE_PLUS_SINE,
+    builtin::SHIFTED_LOGARITHM,
+    // slot for equation uploaded by host. Has to be the last one
     NonLinearEquation {
-        function: |x: TNumber| x.pow(2.) + x + Trigonometry::sin(x),
-        first_derivative: |x: TNumber| 2. * x + 1. + Trigonometry::cos(x),
+        name: solvers::user_equation::NAME,
+        function: user_equation::function,
+        first_derivative: user_equation::first_derivative,
+        second_derivative: Some(user_equation::second_derivative),
     },
-    NonLinearEquation {
-        function: |x: TNumber| Logarithm::ln(x + 15.) as TNumber,
-        first_derivative: |x: TNumber| 1. / (x + 15.),
-    },
-    // NonLinearEquation {
-    //     function: todo!(),
-    //     first_derivative: todo!(),
-    // },
 ];
 
-const SYSTEMS: [SystemOfEquations; 1] = [SystemOfEquations {
-    first: EquationWithPhi {
-        function: |x| (1. - Trigonometry::sin(x) / 2., PointCoordinate::Y),
-        phi: |(_x, y)| 0.7 - Trigonometry::cos(y - 1.),
-    },
-    second: EquationWithPhi {
-        function: |y| (0.7 - Trigonometry::cos(y - 1.), PointCoordinate::X),
-        phi: |(x, _y)| 1. - Trigonometry::sin(x) / 2.,
-    },
-}];
+const SYSTEMS: [SystemOfEquations; 1] = [builtin::SINE_AND_COSINE];
 
 static INITIAL_APPROXIMATIONS: Lazy<UnsafeCell<InitialApproximationsResponse>> = Lazy::new(|| {
     UnsafeCell::new(InitialApproximationsResponse {
@@ -77,6 +63,17 @@ static INITIAL_APPROXIMATIONS: Lazy<UnsafeCell<InitialApproximationsResponse>> =
     })
 });
 
+/// Set when buttons change approximations. Host is notified from the main loop
+static APPROXIMATIONS_CHANGED: Lazy<UnsafeCell<bool>> = Lazy::new(|| UnsafeCell::new(false));
+
+/// New approximations, if they were changed since the last call
+fn take_changed_approximations() -> Option<InitialApproximationsResponse> {
+    without_interrupts(|| {
+        let changed = unsafe { core::mem::replace(&mut *APPROXIMATIONS_CHANGED.get(), false) };
+        changed.then(|| unsafe { *INITIAL_APPROXIMATIONS.get().clone() })
+    })
+}
+
 enum InitialApproximationsEvent {
     LeftUp,
     LeftDown,
@@ -88,32 +85,34 @@ fn update_initial_approximations(event: InitialApproximationsEvent) {
     const STEP: f32 = 0.25;
 
     let approximations = unsafe { &mut *INITIAL_APPROXIMATIONS.get() };
+    let mut updated = *approximations;
     match event {
-        InitialApproximationsEvent::LeftUp => {
-            let new = approximations.left + STEP;
-            if (LEFT_BORDER..RIGHT_BORDER).contains(&new) {
-                approximations.left = new
-            }
-        }
-        InitialApproximationsEvent::LeftDown => {
-            let new = approximations.left - STEP;
-            if (LEFT_BORDER..RIGHT_BORDER).contains(&new) {
-                approximations.left = new
-            }
-        }
-        InitialApproximationsEvent::RightUp => {
-            let new = approximations.right + STEP;
-            if (LEFT_BORDER..RIGHT_BORDER).contains(&new) {
-                approximations.right = new
-            }
-        }
-        InitialApproximationsEvent::RightDown => {
-            let new = approximations.right - STEP;
-            if (LEFT_BORDER..RIGHT_BORDER).contains(&new) {
-                approximations.right = new
-            }
-        }
+        InitialApproximationsEvent::LeftUp => updated.left += STEP,
+        InitialApproximationsEvent::LeftDown => updated.left -= STEP,
+        InitialApproximationsEvent::RightUp => updated.right += STEP,
+        InitialApproximationsEvent::RightDown => updated.right -= STEP,
     }
+
+    // line is not moved out of range or past the other one
+    if is_bracket_valid(updated.left, updated.right) {
+        *approximations = updated;
+        unsafe { *APPROXIMATIONS_CHANGED.get() = true };
+    }
+}
+
+/// Applies approximations requested by host, if they are within range and in order.
+/// Returns approximations in effect
+fn set_initial_approximations(
+    requested: InitialApproximationsResponse,
+) -> InitialApproximationsResponse {
+    without_interrupts(|| {
+        let approximations = unsafe { &mut *INITIAL_APPROXIMATIONS.get() };
+        if is_bracket_valid(requested.left, requested.right) {
+            *approximations = requested;
+        }
+
+        *approximations
+    })
 }
 
 #[no_mangle]
@@ -137,73 +136,71 @@ pub extern "C" fn main() {
         3,
     );
 
-    let mut connection = Connection::new(
-        &*usart::USART,
-        Equations {
-            single: &SINGLE,
-            systems: &SYSTEMS,
-        },
-    );
-    let mut points_handler =
-        |equation: &mut dyn FnMut(TNumber) -> (TNumber, PointCoordinate),
-         write_back: &mut dyn FnMut(Point) -> ()| {
-            for index in 0..POINT_AMOUNT {
-                let variable = LEFT_BORDER + POINT_INTERVAL_LENGTH * index as TNumber;
-                let (dependent, coord) = (equation)(variable);
-                let point = match coord {
-                    PointCoordinate::X => Point::new(dependent, variable),
-                    PointCoordinate::Y => Point::new(variable, dependent),
-                };
-
-                write_back(point);
-            }
-        };
+    let device_info = DeviceInfo {
+        version: PROTOCOL_VERSION,
+        methods: [
+            Method::Chord,
+            Method::Secant,
+            Method::SimpleIterationSingle,
+            Method::Newton,
+            Method::Bisection,
+        ]
+        .iter()
+        .fold(0, |mask, method| mask | method.mask()),
+        system_methods: [SystemMethod::SimpleIteration, SystemMethod::Newton]
+            .iter()
+            .fold(0, |mask, method| mask | method.mask()),
+        features: DeviceInfo::FEATURE_TRACE
+            | DeviceInfo::FEATURE_NOTIFICATIONS
+            | DeviceInfo::FEATURE_FIND_ALL_ROOTS
+            | DeviceInfo::FEATURE_UPLOAD_EQUATION,
+        point_amount: POINT_AMOUNT as u16,
+    };
+
+    let equations = Equations {
+        single: &SINGLE,
+        systems: &SYSTEMS,
+    };
+    let mut connection = Connection::new(&*usart::USART, equations, device_info);
+    let mut points_handler = for_each_point;
 
-    let mut compute_root_handler = |payload: ComputeRootPayload| {
+    let mut compute_root_handler = |payload: ComputeRootPayload, trace: Tracer| {
         let approximations =
             without_interrupts(|| unsafe { *INITIAL_APPROXIMATIONS.get().clone() });
 
-        let parameters = SolverInput {
-            start: approximations.left,
-            end: approximations.right,
-            epsilon: payload.epsilon,
-        };
-
-        match payload.mode {
-            protocol::request::EquationMode::Single(SingleEquation {
-                method,
-                equation_number,
-            }) => {
-                let equation = &SINGLE[equation_number as usize];
-
-                check_roots_in_range(equation, &parameters)?;
-                match method {
-                    Method::Chord => ChordSolver::solve(&ChordSolver, equation, &parameters),
-                    Method::Secant => SecantSolver::solve(&SecantSolver, equation, &parameters),
-                    Method::SimpleIterationSingle => {
-                        SimpleIterationSolver::solve(&SimpleIterationSolver, equation, &parameters)
-                    }
-                }
-            }
-            protocol::request::EquationMode::SystemOfEquations { system_number } => {
-                let system = &SYSTEMS[system_number as usize];
-                SimpleIteratorSolverForSystems::solve(
-                    &SimpleIteratorSolverForSystems,
-                    system,
-                    &parameters,
-                )
-            }
-        }
+        equations.compute_root(payload, approximations, trace)
     };
 
+    let mut find_all_roots_handler =
+        |payload: ComputeRootPayload,
+         write_back: &mut dyn FnMut(Result<ComputeRootResponse, MethodError>)| {
+            equations.find_all_roots(payload, write_back);
+        };
+
     let mut initial_approximations_handler =
         || without_interrupts(|| unsafe { *INITIAL_APPROXIMATIONS.get().clone() });
     connection.set_points_handler(&mut points_handler);
     connection.set_initial_approximation(&mut initial_approximations_handler);
     connection.set_compute_root(&mut compute_root_handler);
+    let mut set_initial_approximations_handler = set_initial_approximations;
+    connection.set_initial_approximations(&mut set_initial_approximations_handler);
+    connection.set_find_all_roots(&mut find_all_roots_handler);
+    let mut upload = ProgramAssembler::new();
+    let mut upload_equation_handler = |chunk: UploadChunk| {
+        if let Some(program) = upload.push(&chunk)? {
+            user_equation::store(chunk.target, program);
+        }
+
+        Ok(())
+    };
+    connection.set_upload_equation(&mut upload_equation_handler);
 
     loop {
-        connection.handle_request();
+        if let Some(approximations) = take_changed_approximations() {
+            connection.notify(Notification::InitialApproximationsChanged(approximations));
+        }
+
+        connection.poll_request();
     }
 }
 
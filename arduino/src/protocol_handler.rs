@@ -1,43 +1,67 @@
 use core::mem::size_of;
 use protocol::byte_serializable::ByteSerializable;
+use protocol::catalogue::EquationCatalogue;
+use protocol::decode_error::DecodeError;
+use protocol::device_info::DeviceInfo;
+use protocol::frame::{
+    sized_payload, write_frame, FrameDecoder, FrameHeader, FrameWriter, MessageType,
+    UNSOLICITED_SEQUENCE,
+};
+use protocol::notification::Notification;
 use protocol::point::Point;
 use protocol::point::PointCoordinate;
-use protocol::request::payloads::ComputeRootPayload;
+use protocol::request::payloads::{ComputeRootPayload, UploadChunk};
 use protocol::request::RequestPackage;
 use protocol::response::ComputeRootResponse;
 use protocol::response::InitialApproximationsResponse;
 use protocol::response::MethodError;
+use protocol::response::TraceRecord;
 use protocol::TNumber;
-use protocol::PACKAGE_SIZE;
+use protocol::{LONG_PACKAGE_SIZE, PACKAGE_SIZE};
 
 use ruduino::{cores::current::USART0, modules::HardwareUsart};
 
-use crate::equations::Equations;
 use crate::usart::Usart;
+use solvers::{Equations, Tracer};
 
 type PointsHandler<'a> = &'a mut dyn FnMut(
     &mut dyn FnMut(TNumber) -> (TNumber, PointCoordinate),
     &mut dyn FnMut(Point) -> (),
 ) -> ();
 type InitialApproximationHandler<'b> = &'b mut dyn FnMut() -> InitialApproximationsResponse;
-type ComputeRootHandler<'c> = &'c mut dyn FnMut(ComputeRootPayload) -> Result<Point, MethodError>;
+type ComputeRootHandler<'c> =
+    &'c mut dyn FnMut(ComputeRootPayload, Tracer) -> Result<ComputeRootResponse, MethodError>;
+/// Returns approximations in effect
+type SetInitialApproximationsHandler<'d> =
+    &'d mut dyn FnMut(InitialApproximationsResponse) -> InitialApproximationsResponse;
+/// Writes back result per isolated root
+type FindAllRootsHandler<'e> =
+    &'e mut dyn FnMut(ComputeRootPayload, &mut dyn FnMut(Result<ComputeRootResponse, MethodError>));
+/// Fails if chunk is out of order or assembled program is broken
+type UploadEquationHandler<'f> = &'f mut dyn FnMut(UploadChunk) -> Result<(), DecodeError>;
 
-pub struct Connection<'aa, 'a, 'b, 'c, T: HardwareUsart> {
+pub struct Connection<'aa, 'a, 'b, 'c, 'd, 'e, 'f, T: HardwareUsart> {
     channel: &'aa Usart<T>,
     function_points_handler: Option<PointsHandler<'a>>,
     function_initial_approximation: Option<InitialApproximationHandler<'b>>,
     function_compute_root: Option<ComputeRootHandler<'c>>,
+    function_set_initial_approximations: Option<SetInitialApproximationsHandler<'d>>,
+    function_find_all_roots: Option<FindAllRootsHandler<'e>>,
+    function_upload_equation: Option<UploadEquationHandler<'f>>,
     context: Equations,
+    decoder: FrameDecoder<PACKAGE_SIZE>,
 }
 
-impl<'aa, 'a, 'b, 'c> Connection<'aa, 'a, 'b, 'c, USART0> {
+impl<'aa, 'a, 'b, 'c, 'd, 'e, 'f> Connection<'aa, 'a, 'b, 'c, 'd, 'e, 'f, USART0> {
     // send protocol signature
     // when correct protocol singature is echoed back
+    // tell what firmware is capable of
     // await for requests
     pub fn new(
         channel: &'aa Usart<USART0>,
         context: Equations,
-    ) -> Connection<'aa, 'a, 'b, 'c, USART0> {
+        device_info: DeviceInfo,
+    ) -> Connection<'aa, 'a, 'b, 'c, 'd, 'e, 'f, USART0> {
         // for some reason when arduino is first plugged in
         // it sends 0xfe, 0xfd or 0xff byte before the protocol signature.
         // Noticable, that if only two bytes are sent at a time, no additional bytes
@@ -59,58 +83,191 @@ impl<'aa, 'a, 'b, 'c> Connection<'aa, 'a, 'b, 'c, USART0> {
             }
         }
 
+        write_frame(
+            |bytes| channel.write_blocking(bytes),
+            MessageType::Response,
+            UNSOLICITED_SEQUENCE,
+            &device_info.to_bytes(),
+        );
+
         Connection {
             channel,
             function_points_handler: None,
             function_initial_approximation: None,
             function_compute_root: None,
+            function_set_initial_approximations: None,
+            function_find_all_roots: None,
+            function_upload_equation: None,
             context,
+            decoder: FrameDecoder::new(),
         }
     }
 
-    pub fn handle_request(&mut self) {
-        let mut incoming_data = [0_u8; PACKAGE_SIZE];
-        self.channel.read_blocking(&mut incoming_data);
+    /// Handles request if it has arrived. Returns immediately otherwise
+    pub fn poll_request(&mut self) {
+        let (sequence, request) = match self.receive_request() {
+            None => return,
+            Some(Ok(request)) => request,
+            Some(Err(error)) => {
+                write_frame(
+                    |bytes| self.channel.write_blocking(bytes),
+                    MessageType::Error,
+                    self.decoder.sequence(),
+                    &error.to_bytes(),
+                );
+                return;
+            }
+        };
+
+        let channel = self.channel;
+        let respond = |payload: &[u8]| {
+            write_frame(
+                |bytes| channel.write_blocking(bytes),
+                MessageType::Response,
+                sequence,
+                payload,
+            )
+        };
 
-        let request = RequestPackage::from_bytes(&incoming_data);
         match request {
             RequestPackage::FunctionPoints { payload } => {
                 if let Some(handler) = &mut self.function_points_handler {
-                    let mut writer = |point: Point| self.channel.write_blocking(&point.to_bytes());
+                    // points are sent as they are computed. There is no room
+                    // to keep all of them
+                    let frame = || {
+                        FrameWriter::new(
+                            |bytes: &[u8]| channel.write_blocking(bytes),
+                            MessageType::Response,
+                            sequence,
+                            LONG_PACKAGE_SIZE as u16,
+                        )
+                    };
                     match payload.mode {
                         protocol::request::EquationModeRaw::SingleEquation => {
                             let equation = &self.context.single[payload.index as usize];
+                            let mut writer = frame();
                             handler(
                                 &mut |x| ((equation.function)(x), PointCoordinate::Y),
-                                &mut writer,
+                                &mut |point: Point| writer.write(&point.to_bytes()),
                             );
+                            writer.finish();
                         }
                         protocol::request::EquationModeRaw::SystemOfEquations => {
-                            let mut system =
-                                self.context.systems[payload.index as usize].clone();
-                            handler(&mut system.first.function, &mut writer);
-                            handler(&mut system.second.function, &mut writer);
+                            let mut system = self.context.systems[payload.index as usize].clone();
+                            for function in
+                                [&mut system.first.function, &mut system.second.function]
+                            {
+                                let mut writer = frame();
+                                handler(function, &mut |point: Point| {
+                                    writer.write(&point.to_bytes())
+                                });
+                                writer.finish();
+                            }
                         }
                     };
                 }
             }
             RequestPackage::InitialApproximations => {
                 if let Some(handler) = &mut self.function_initial_approximation {
-                    let bytes = handler().to_bytes();
-                    self.channel.write_blocking(&bytes);
+                    respond(&handler().to_bytes());
                 }
             }
             RequestPackage::ComputeRoot { payload } => {
                 if let Some(handler) = &mut self.function_compute_root {
-                    let bytes = handler(payload)
-                        .map(|point| ComputeRootResponse { root: point })
-                        .to_bytes();
-                    self.channel.write_blocking(&bytes);
+                    respond(&handler(payload, &mut |_| ()).to_bytes());
+                }
+            }
+            RequestPackage::ListEquations => self.write_catalogue(sequence),
+            RequestPackage::SetInitialApproximations { left, right } => {
+                if let Some(handler) = &mut self.function_set_initial_approximations {
+                    respond(&handler(InitialApproximationsResponse { left, right }).to_bytes());
+                }
+            }
+            RequestPackage::FindAllRoots { payload } => {
+                if let Some(handler) = &mut self.function_find_all_roots {
+                    // every root is a frame on its own. Empty one ends the list
+                    handler(payload, &mut |root| respond(&root.to_bytes()));
+                    respond(&[]);
+                }
+            }
+            RequestPackage::UploadEquation { payload } => {
+                if let Some(handler) = &mut self.function_upload_equation {
+                    match handler(payload) {
+                        Ok(()) => respond(&[]),
+                        Err(error) => write_frame(
+                            |bytes| channel.write_blocking(bytes),
+                            MessageType::Error,
+                            sequence,
+                            &error.to_bytes(),
+                        ),
+                    }
+                }
+            }
+            RequestPackage::ComputeRootTrace { payload } => {
+                if let Some(handler) = &mut self.function_compute_root {
+                    // every record is a frame on its own
+                    let mut writer = |record: TraceRecord| respond(&record.to_bytes());
+                    let result = handler(payload, &mut writer);
+
+                    respond(&TraceRecord::terminator().to_bytes());
+                    respond(&result.to_bytes());
                 }
             }
         }
     }
 
+    fn write_catalogue(&self, sequence: u8) {
+        let Equations { single, systems } = self.context;
+        let names = || {
+            single.iter().map(|equation| equation.name).chain(
+                systems
+                    .iter()
+                    .flat_map(|system| [system.first.name, system.second.name]),
+            )
+        };
+
+        let mut writer = FrameWriter::new(
+            |bytes: &[u8]| self.channel.write_blocking(bytes),
+            MessageType::Response,
+            sequence,
+            EquationCatalogue::encoded_size(names()) as u16,
+        );
+        EquationCatalogue::encode(single.len() as u8, systems.len() as u8, names(), |bytes| {
+            writer.write(bytes)
+        });
+        writer.finish();
+    }
+
+    /// Event which host did not ask about
+    pub fn notify(&self, notification: Notification) {
+        write_frame(
+            |bytes| self.channel.write_blocking(bytes),
+            MessageType::Notification,
+            UNSOLICITED_SEQUENCE,
+            &notification.to_bytes(),
+        );
+    }
+
+    /// Consumes bytes received so far. `None` if request frame is not complete yet
+    fn receive_request(&mut self) -> Option<Result<(u8, RequestPackage), DecodeError>> {
+        let result = self.decoder.next_frame(|| self.channel.read_byte().ok())?;
+        Some(result.and_then(|header| self.decode_request(header)))
+    }
+
+    fn decode_request(&self, header: FrameHeader) -> Result<(u8, RequestPackage), DecodeError> {
+        if header.message_type != MessageType::Request {
+            return Err(DecodeError::UnexpectedMessageType(
+                header.message_type.to_byte(),
+            ));
+        }
+
+        let payload = sized_payload::<PACKAGE_SIZE>(self.decoder.payload())?;
+        let request = RequestPackage::try_from_bytes(payload)?;
+        self.context.check_request(&request)?;
+
+        Ok((header.sequence, request))
+    }
+
     pub fn set_points_handler(&mut self, handler: PointsHandler<'a>) {
         self.function_points_handler = Some(handler);
     }
@@ -120,4 +277,13 @@ impl<'aa, 'a, 'b, 'c> Connection<'aa, 'a, 'b, 'c, USART0> {
     pub fn set_compute_root(&mut self, handler: ComputeRootHandler<'c>) {
         self.function_compute_root = Some(handler);
     }
+    pub fn set_initial_approximations(&mut self, handler: SetInitialApproximationsHandler<'d>) {
+        self.function_set_initial_approximations = Some(handler);
+    }
+    pub fn set_find_all_roots(&mut self, handler: FindAllRootsHandler<'e>) {
+        self.function_find_all_roots = Some(handler);
+    }
+    pub fn set_upload_equation(&mut self, handler: UploadEquationHandler<'f>) {
+        self.function_upload_equation = Some(handler);
+    }
 }
@@ -1,6 +1,7 @@
 use core::arch::asm;
 use core::cell::UnsafeCell;
 
+use protocol::BAUD_RATE;
 use ruduino::cores::current::SREG;
 use ruduino::cores::current::UCSR0A;
 use ruduino::cores::current::UCSR0B;
@@ -68,8 +69,11 @@ pub enum UsartError {
 }
 
 // mutex is not necessary, because we are in single thread environment
-pub static USART: Lazy<Usart<USART0>> =
-    Lazy::new(|| Usart::configre(UsartConfig { baud_rate: 250_000 }));
+pub static USART: Lazy<Usart<USART0>> = Lazy::new(|| {
+    Usart::configre(UsartConfig {
+        baud_rate: BAUD_RATE,
+    })
+});
 
 impl Usart<USART0> {
     fn configre(UsartConfig { baud_rate }: UsartConfig) -> Usart<USART0> {
@@ -0,0 +1,34 @@
+//! Equation uploaded by host. Takes the last slot of single equations,
+//! so every solver and the points handler use it as any other equation
+
+use core::cell::UnsafeCell;
+
+use protocol::expression::{Program, ProgramTarget};
+use protocol::TNumber;
+
+use crate::lazy::Lazy;
+use solvers::user_equation::UserEquation;
+
+/// Accessed from the main loop only
+static USER_EQUATION: Lazy<UnsafeCell<UserEquation>> =
+    Lazy::new(|| UnsafeCell::new(UserEquation::new()));
+
+fn equation() -> &'static mut UserEquation {
+    unsafe { &mut *USER_EQUATION.get() }
+}
+
+pub fn store(target: ProgramTarget, program: Program) {
+    equation().store(target, program);
+}
+
+pub fn function(x: TNumber) -> TNumber {
+    equation().function(x)
+}
+
+pub fn first_derivative(x: TNumber) -> TNumber {
+    equation().first_derivative(x)
+}
+
+pub fn second_derivative(x: TNumber) -> TNumber {
+    equation().second_derivative(x)
+}
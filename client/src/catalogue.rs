@@ -0,0 +1,21 @@
+use protocol::catalogue::EquationCatalogue;
+
+/// Names of equations flashed into arduino
+#[derive(Debug, Clone, Default)]
+pub struct Catalogue {
+    pub single: Vec<String>,
+    /// Names of the first and the second equation of the system
+    pub systems: Vec<(String, String)>,
+}
+
+impl From<EquationCatalogue<'_>> for Catalogue {
+    fn from(value: EquationCatalogue<'_>) -> Self {
+        Self {
+            single: value.single().map(String::from).collect(),
+            systems: value
+                .systems()
+                .map(|(first, second)| (first.to_owned(), second.to_owned()))
+                .collect(),
+        }
+    }
+}
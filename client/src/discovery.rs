@@ -0,0 +1,51 @@
+//! Looks for the port arduino is plugged in. It is enumerated
+//! as `ttyACM0`, `ttyACM1` or `ttyUSB0` depending on the machine
+
+use std::fmt::Display;
+
+use futures::future::join_all;
+use tokio_serial::SerialPortBuilderExt;
+
+use crate::{ClientError, Device, Stream};
+
+/// Port listed by system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortCandidate {
+    pub path: String,
+    /// Protocol signature was received from the port
+    pub responds: bool,
+}
+
+impl Display for PortCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.responds {
+            true => write!(f, "{}", self.path),
+            false => write!(f, "{} (no signature)", self.path),
+        }
+    }
+}
+
+pub fn open(path: &str, baud_rate: u32) -> Result<Stream, ClientError> {
+    Ok(Box::new(
+        tokio_serial::new(path, baud_rate).open_native_async()?,
+    ))
+}
+
+/// Probes every available port at once.
+/// Devices which responded are ready to be used
+pub async fn discover(baud_rate: u32) -> Result<Vec<(PortCandidate, Option<Device>)>, ClientError> {
+    let paths = tokio_serial::available_ports()?
+        .into_iter()
+        .map(|port| port.port_name);
+    let probes = paths.map(|path| async move {
+        let device = Device::connect(&path, baud_rate).await.ok();
+        let candidate = PortCandidate {
+            path,
+            responds: device.is_some(),
+        };
+
+        (candidate, device)
+    });
+
+    Ok(join_all(probes).await)
+}
@@ -0,0 +1,69 @@
+use std::fmt::Display;
+use std::io;
+
+use protocol::decode_error::DecodeError;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// Port is gone. Device is unplugged most likely
+    Io(io::Error),
+    /// Something else is plugged in the port
+    InvalidSignature,
+    /// Device did not answer in time. Response is considered lost
+    Timeout,
+    /// Response is broken
+    Decode(DecodeError),
+    /// Device could not decode request. Reason is sent by device, if any
+    Rejected(Option<DecodeError>),
+}
+
+impl ClientError {
+    /// There is no point to send requests anymore, link has to be reestablished
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, ClientError::Io(_))
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Io(error) => write!(f, "{}", error),
+            ClientError::InvalidSignature => write!(
+                f,
+                "Signature is not valid. Is this device sertified by White Horizont corporation?"
+            ),
+            ClientError::Timeout => write!(f, "device did not respond in time"),
+            ClientError::Decode(error) => write!(f, "broken response: {}", error),
+            ClientError::Rejected(Some(error)) => {
+                write!(f, "device could not decode request: {}", error)
+            }
+            ClientError::Rejected(None) => write!(f, "device could not decode request"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(value: io::Error) -> Self {
+        ClientError::Io(value)
+    }
+}
+
+impl From<tokio_serial::Error> for ClientError {
+    fn from(value: tokio_serial::Error) -> Self {
+        ClientError::Io(value.into())
+    }
+}
+
+impl From<DecodeError> for ClientError {
+    fn from(value: DecodeError) -> Self {
+        ClientError::Decode(value)
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for ClientError {
+    fn from(_: tokio::time::error::Elapsed) -> Self {
+        ClientError::Timeout
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use protocol::byte_serializable::ByteSerializable;
+use protocol::decode_error::DecodeError;
+use protocol::frame::{sized_payload, FrameDecoder, FrameHeader, MessageType};
+use protocol::notification::Notification;
+use protocol::LONG_PACKAGE_SIZE;
+use tokio::io::AsyncReadExt;
+
+use crate::error::ClientError;
+use crate::Stream;
+
+const READ_CHUNK_SIZE: usize = 256;
+
+/// Assembles frames out of the serial port stream
+pub(crate) struct FrameReader {
+    decoder: FrameDecoder<LONG_PACKAGE_SIZE>,
+    buffer: [u8; READ_CHUNK_SIZE],
+    start: usize,
+    end: usize,
+    /// Notifications which arrived while waiting for a response
+    notifications: VecDeque<Notification>,
+    /// Frames skipped so far, see [`dropped_frames`](FrameReader::dropped_frames)
+    dropped: usize,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        Self {
+            decoder: FrameDecoder::new(),
+            buffer: [0; READ_CHUNK_SIZE],
+            start: 0,
+            end: 0,
+            notifications: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Broken frames, undecodable notifications and requests sent by device.
+    /// They are skipped without failing the request
+    pub fn dropped_frames(&self) -> usize {
+        self.dropped
+    }
+
+    /// Waits for the next intact frame. Noise and broken frames are skipped.
+    /// Cancel safe: partially received frame is kept until the next call
+    async fn read_frame(&mut self, stream: &mut Stream) -> io::Result<FrameHeader> {
+        loop {
+            let next_byte = || {
+                let byte = self.buffer[self.start..self.end].first().copied();
+                self.start += byte.is_some() as usize;
+                byte
+            };
+            match self.decoder.next_frame(next_byte) {
+                Some(Ok(header)) => return Ok(header),
+                Some(Err(_)) => {
+                    self.dropped += 1;
+                    continue;
+                }
+                None => (),
+            }
+
+            let read = stream.read(&mut self.buffer).await?;
+            if read == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+
+            self.start = 0;
+            self.end = read;
+        }
+    }
+
+    /// Payload of the response to the request with `sequence` number.
+    /// Fails if no frame arrives within `timeout`.
+    /// Late responses to earlier requests are skipped. Notifications are
+    /// queued for [`read_notification`](FrameReader::read_notification)
+    pub async fn read_response(
+        &mut self,
+        stream: &mut Stream,
+        sequence: u8,
+        timeout: Duration,
+    ) -> Result<&[u8], ClientError> {
+        loop {
+            let header = tokio::time::timeout(timeout, self.read_frame(stream))
+                .await
+                .inspect_err(|_| self.decoder.reset())??;
+
+            if header.message_type == MessageType::Notification {
+                match self.decode_notification() {
+                    Ok(notification) => self.notifications.push_back(notification),
+                    Err(_) => self.dropped += 1,
+                }
+                continue;
+            }
+
+            if header.sequence != sequence {
+                continue;
+            }
+
+            match header.message_type {
+                MessageType::Response => return Ok(self.decoder.payload()),
+                MessageType::Error => {
+                    let error = sized_payload(self.decoder.payload())
+                        .ok()
+                        .and_then(DecodeError::from_bytes);
+                    return Err(ClientError::Rejected(error));
+                }
+                // device is not expected to send requests
+                MessageType::Request => self.dropped += 1,
+                MessageType::Notification => unreachable!("notifications are queued above"),
+            }
+        }
+    }
+
+    /// Waits for the next notification without any timeout.
+    /// Queued notifications go first. Late responses are skipped
+    pub async fn read_notification(
+        &mut self,
+        stream: &mut Stream,
+    ) -> Result<Notification, ClientError> {
+        if let Some(notification) = self.notifications.pop_front() {
+            return Ok(notification);
+        }
+
+        loop {
+            let header = self.read_frame(stream).await?;
+            if header.message_type == MessageType::Notification {
+                return Ok(self.decode_notification()?);
+            }
+        }
+    }
+
+    fn decode_notification(&self) -> Result<Notification, DecodeError> {
+        Notification::try_from_bytes(sized_payload(self.decoder.payload())?)
+    }
+}
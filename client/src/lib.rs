@@ -0,0 +1,277 @@
+//! Talks to the device over serial port or any other byte stream.
+//! Every request has a method which awaits the whole response
+//! and returns it decoded
+
+use std::time::Duration;
+
+use frame_reader::FrameReader;
+use protocol::byte_serializable::ByteSerializable;
+use protocol::catalogue::EquationCatalogue;
+use protocol::device_info::DeviceInfo;
+use protocol::frame::{
+    sized_payload, write_frame, MessageType, FRAME_OVERHEAD, UNSOLICITED_SEQUENCE,
+};
+use protocol::notification::Notification;
+use protocol::request::payloads::{ComputeRootPayload, UploadChunk};
+use protocol::request::{EquationModeRaw, RequestPackage, Selection};
+use protocol::response::{
+    ComputeRootResponse, FunctionPointsResponse, InitialApproximationsResponse, MethodError,
+    TraceRecord,
+};
+use protocol::{
+    is_signature_valid, TNumber, PACKAGE_SIZE, PROTOCOL_SIGNATURE, PROTOCOL_SIGNATURE_SIZE,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub use catalogue::Catalogue;
+pub use error::ClientError;
+
+pub mod catalogue;
+pub mod discovery;
+mod error;
+mod frame_reader;
+
+/// Arduino reboots once port is opened, so signature takes a while
+const SIGNATURE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Device should answer within this period. Otherwise response is considered lost
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Upper bound of a single method iteration on arduino. Methods answer
+/// only once they are done, so their timeout grows with `max_iterations`
+const ITERATION_DURATION: Duration = Duration::from_millis(5);
+
+/// Byte stream device is reached through
+pub trait DeviceStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> DeviceStream for T {}
+
+pub type Stream = Box<dyn DeviceStream>;
+
+/// Iterations of the method followed by its result
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub records: Vec<TraceRecord>,
+    pub result: Result<ComputeRootResponse, MethodError>,
+}
+
+/// Device with verified signature. Requests are answered one by one
+pub struct Device {
+    stream: Stream,
+    frames: FrameReader,
+    /// Sequence number of the last request.
+    /// Starts from [`UNSOLICITED_SEQUENCE`]
+    sequence: u8,
+    /// How long the last request may take to be answered
+    response_timeout: Duration,
+    info: DeviceInfo,
+}
+
+impl Device {
+    /// Opens serial port and exchanges signature
+    pub async fn connect(path: &str, baud_rate: u32) -> Result<Self, ClientError> {
+        Self::handshake(discovery::open(path, baud_rate)?).await
+    }
+
+    /// Exchanges signature and receives device info. Fails if device stays silent
+    pub async fn handshake(stream: Stream) -> Result<Self, ClientError> {
+        let mut stream =
+            tokio::time::timeout(SIGNATURE_TIMEOUT, verify_signature(stream)).await??;
+        let mut frames = FrameReader::new();
+        let payload = frames
+            .read_response(&mut stream, UNSOLICITED_SEQUENCE, RESPONSE_TIMEOUT)
+            .await?;
+        let info = DeviceInfo::try_from_bytes(sized_payload(payload)?)?;
+
+        Ok(Self {
+            stream,
+            frames,
+            sequence: UNSOLICITED_SEQUENCE,
+            response_timeout: RESPONSE_TIMEOUT,
+            info,
+        })
+    }
+
+    /// Sent by device right after the handshake. Check
+    /// [`is_compatible`](DeviceInfo::is_compatible) before making requests
+    pub fn info(&self) -> DeviceInfo {
+        self.info
+    }
+
+    pub async fn list_equations(&mut self) -> Result<Catalogue, ClientError> {
+        self.send(RequestPackage::ListEquations).await?;
+        let payload = self.response().await?;
+        Ok(EquationCatalogue::try_from_bytes(payload)?.into())
+    }
+
+    /// Curve per equation: one for single equation and two for system
+    pub async fn function_points(
+        &mut self,
+        selection: Selection,
+    ) -> Result<Vec<FunctionPointsResponse>, ClientError> {
+        self.send(RequestPackage::FunctionPoints { payload: selection })
+            .await?;
+        let amount = match selection.mode {
+            EquationModeRaw::SingleEquation => 1,
+            EquationModeRaw::SystemOfEquations => 2,
+        };
+
+        let mut curves = Vec::with_capacity(amount);
+        for _ in 0..amount {
+            let payload = self.response().await?;
+            curves.push(FunctionPointsResponse::try_from(sized_payload(payload)?)?);
+        }
+
+        Ok(curves)
+    }
+
+    pub async fn initial_approximations(
+        &mut self,
+    ) -> Result<InitialApproximationsResponse, ClientError> {
+        self.send(RequestPackage::InitialApproximations).await?;
+        self.read().await
+    }
+
+    /// Approximations in effect. They are left unchanged if requested ones are out of range
+    pub async fn set_initial_approximations(
+        &mut self,
+        left: TNumber,
+        right: TNumber,
+    ) -> Result<InitialApproximationsResponse, ClientError> {
+        self.send(RequestPackage::SetInitialApproximations { left, right })
+            .await?;
+        self.read().await
+    }
+
+    pub async fn compute_root(
+        &mut self,
+        payload: ComputeRootPayload,
+    ) -> Result<Result<ComputeRootResponse, MethodError>, ClientError> {
+        self.send(RequestPackage::ComputeRoot { payload }).await?;
+        self.read().await
+    }
+
+    pub async fn compute_root_trace(
+        &mut self,
+        payload: ComputeRootPayload,
+    ) -> Result<Trace, ClientError> {
+        self.send(RequestPackage::ComputeRootTrace { payload })
+            .await?;
+        let mut records = Vec::new();
+        loop {
+            let record: TraceRecord = self.read().await?;
+            if record.is_terminator() {
+                break;
+            }
+
+            records.push(record);
+        }
+
+        Ok(Trace {
+            records,
+            result: self.read().await?,
+        })
+    }
+
+    /// Root per interval with sign change
+    pub async fn find_all_roots(
+        &mut self,
+        payload: ComputeRootPayload,
+    ) -> Result<Vec<Result<ComputeRootResponse, MethodError>>, ClientError> {
+        self.send(RequestPackage::FindAllRoots { payload }).await?;
+        let mut roots = Vec::new();
+        // a frame per isolated root, empty one ends the list
+        loop {
+            let payload = self.response().await?;
+            if payload.is_empty() {
+                break;
+            }
+
+            roots.push(Result::<ComputeRootResponse, MethodError>::try_from_bytes(
+                sized_payload(payload)?,
+            )?);
+        }
+
+        Ok(roots)
+    }
+
+    /// Broken program is reported as [`ClientError::Rejected`]
+    pub async fn upload_equation(&mut self, chunk: UploadChunk) -> Result<(), ClientError> {
+        self.send(RequestPackage::UploadEquation { payload: chunk })
+            .await?;
+        // acknowledgement is empty
+        self.response().await?;
+        Ok(())
+    }
+
+    /// Frames skipped since handshake: broken ones, undecodable notifications
+    /// and requests sent by device. Grows on a noisy link
+    pub fn dropped_frames(&self) -> usize {
+        self.frames.dropped_frames()
+    }
+
+    /// Waits for the next notification without any timeout.
+    /// Cancel safe, so it is awaited along with requests to be sent
+    pub async fn notification(&mut self) -> Result<Notification, ClientError> {
+        self.frames.read_notification(&mut self.stream).await
+    }
+
+    /// Sends request with the next sequence number
+    async fn send(&mut self, request: RequestPackage) -> Result<(), ClientError> {
+        // handshake sequence is reserved for device info
+        self.sequence = match self.sequence.wrapping_add(1) {
+            UNSOLICITED_SEQUENCE => UNSOLICITED_SEQUENCE + 1,
+            next => next,
+        };
+        self.response_timeout = response_timeout(&request);
+        let mut frame = Vec::with_capacity(PACKAGE_SIZE + FRAME_OVERHEAD);
+        write_frame(
+            |bytes| frame.extend_from_slice(bytes),
+            MessageType::Request,
+            self.sequence,
+            &request.to_bytes(),
+        );
+
+        Ok(self.stream.write_all(&frame).await?)
+    }
+
+    /// Payload of the next response to the last request
+    async fn response(&mut self) -> Result<&[u8], ClientError> {
+        self.frames
+            .read_response(&mut self.stream, self.sequence, self.response_timeout)
+            .await
+    }
+
+    async fn read<T: ByteSerializable<SIZE>, const SIZE: usize>(
+        &mut self,
+    ) -> Result<T, ClientError> {
+        let payload = self.response().await?;
+        Ok(T::try_from_bytes(sized_payload(payload)?)?)
+    }
+}
+
+/// Time device may take to answer `request`
+fn response_timeout(request: &RequestPackage) -> Duration {
+    match request {
+        RequestPackage::ComputeRoot { payload } | RequestPackage::FindAllRoots { payload } => {
+            RESPONSE_TIMEOUT + ITERATION_DURATION * payload.max_iterations as u32
+        }
+        // trace record follows every iteration
+        _ => RESPONSE_TIMEOUT,
+    }
+}
+
+async fn verify_signature(mut stream: Stream) -> Result<Stream, ClientError> {
+    // if signature was not received within first 16 bytes, then connection
+    // could not be insteblished
+    let mut buffer = [0; PROTOCOL_SIGNATURE_SIZE * 2];
+
+    stream.read_exact(&mut buffer).await?;
+    if !is_signature_valid(&buffer) {
+        return Err(ClientError::InvalidSignature);
+    }
+    stream.write_all(&PROTOCOL_SIGNATURE.to_le_bytes()).await?;
+    stream.flush().await?;
+
+    // leftovers of the signature are skipped by frame decoder
+
+    Ok(stream)
+}
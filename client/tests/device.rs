@@ -0,0 +1,236 @@
+use client::{ClientError, Device, Stream};
+use protocol::byte_serializable::ByteSerializable;
+use protocol::decode_error::DecodeError;
+use protocol::device_info::DeviceInfo;
+use protocol::frame::{
+    sized_payload, write_frame, FrameDecoder, FrameWriter, MessageType, UNSOLICITED_SEQUENCE,
+};
+use protocol::notification::Notification;
+use protocol::point::Point;
+use protocol::request::compute_method::Method;
+use protocol::request::payloads::{ComputeRootPayload, StoppingCriterion};
+use protocol::request::{EquationMode, EquationModeRaw, RequestPackage, Selection, SingleEquation};
+use protocol::response::{InitialApproximationsResponse, MethodError};
+use protocol::{
+    TNumber, LONG_PACKAGE_SIZE, PACKAGE_SIZE, POINT_AMOUNT, PROTOCOL_SIGNATURE,
+    PROTOCOL_SIGNATURE_SIZE, PROTOCOL_VERSION,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+const INFO: DeviceInfo = DeviceInfo {
+    version: PROTOCOL_VERSION,
+    methods: 0xff,
+    system_methods: 0xff,
+    features: 0,
+    point_amount: POINT_AMOUNT as u16,
+};
+
+const APPROXIMATIONS: InitialApproximationsResponse = InitialApproximationsResponse {
+    left: -2.,
+    right: 3.,
+};
+
+/// Answers every request with frames written by `answer`
+fn spawn_device(
+    mut answer: impl FnMut(u8, RequestPackage, &mut Vec<u8>) + Send + 'static,
+) -> Stream {
+    let (host, mut device) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let signature = PROTOCOL_SIGNATURE.to_le_bytes();
+        device.write_all(&signature).await?;
+        device.write_all(&signature).await?;
+        let mut echo = [0; PROTOCOL_SIGNATURE_SIZE];
+        device.read_exact(&mut echo).await?;
+
+        let mut output = Vec::new();
+        respond(&mut output, UNSOLICITED_SEQUENCE, &INFO.to_bytes());
+        device.write_all(&output).await?;
+
+        serve(device, &mut answer).await
+    });
+
+    Box::new(host)
+}
+
+async fn serve(
+    mut device: DuplexStream,
+    answer: &mut impl FnMut(u8, RequestPackage, &mut Vec<u8>),
+) -> std::io::Result<()> {
+    let mut decoder = FrameDecoder::<PACKAGE_SIZE>::new();
+    let mut byte = [0];
+    loop {
+        device.read_exact(&mut byte).await?;
+        let mut next_byte = Some(byte[0]);
+        let Some(Ok(header)) = decoder.next_frame(|| next_byte.take()) else {
+            continue;
+        };
+
+        let payload = sized_payload(decoder.payload()).expect("request is of fixed size");
+        let request = RequestPackage::try_from_bytes(payload).expect("request is valid");
+        let mut output = Vec::new();
+        answer(header.sequence, request, &mut output);
+        device.write_all(&output).await?;
+    }
+}
+
+fn respond(output: &mut Vec<u8>, sequence: u8, payload: &[u8]) {
+    write_frame(
+        |bytes| output.extend_from_slice(bytes),
+        MessageType::Response,
+        sequence,
+        payload,
+    );
+}
+
+fn respond_points(output: &mut Vec<u8>, sequence: u8, y: TNumber) {
+    let mut writer = FrameWriter::new(
+        |bytes: &[u8]| output.extend_from_slice(bytes),
+        MessageType::Response,
+        sequence,
+        LONG_PACKAGE_SIZE as u16,
+    );
+    for index in 0..POINT_AMOUNT {
+        writer.write(&Point::new(index as TNumber, y).to_bytes());
+    }
+    writer.finish();
+}
+
+fn compute_root_payload() -> ComputeRootPayload {
+    ComputeRootPayload {
+        epsilon: 1e-3,
+        mode: EquationMode::Single(SingleEquation {
+            method: Method::Newton,
+            equation_number: 0,
+        }),
+        max_iterations: 100,
+        criterion: StoppingCriterion::Step,
+    }
+}
+
+#[tokio::test]
+async fn handshake_receives_device_info() {
+    let device = Device::handshake(spawn_device(|_, _, _| ())).await.unwrap();
+
+    assert_eq!(device.info(), INFO);
+}
+
+#[tokio::test]
+async fn silent_stream_is_not_a_device() {
+    let (host, mut other) = tokio::io::duplex(64);
+    tokio::spawn(async move { other.write_all(&[0; 16]).await });
+
+    let result = Device::handshake(Box::new(host)).await;
+
+    assert!(matches!(result, Err(ClientError::InvalidSignature)));
+}
+
+#[tokio::test]
+async fn compute_root_returns_method_error() {
+    let error = MethodError::NoRootInRange {
+        suggestion: Some(APPROXIMATIONS),
+    };
+    let stream = spawn_device(move |sequence, request, output| {
+        assert!(matches!(request, RequestPackage::ComputeRoot { .. }));
+        respond(output, sequence, &Err(error).to_bytes());
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let result = device.compute_root(compute_root_payload()).await.unwrap();
+
+    assert_eq!(result.unwrap_err(), error);
+}
+
+#[tokio::test]
+async fn system_has_curve_per_equation() {
+    let stream = spawn_device(|sequence, _, output| {
+        respond_points(output, sequence, 1.);
+        respond_points(output, sequence, 2.);
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let curves = device
+        .function_points(Selection {
+            mode: EquationModeRaw::SystemOfEquations,
+            index: 0,
+        })
+        .await
+        .unwrap();
+
+    let heights: Vec<TNumber> = curves.iter().map(|curve| curve.0[0].y).collect();
+    assert_eq!(heights, [1., 2.]);
+}
+
+#[tokio::test]
+async fn notification_before_response_is_kept() {
+    let changed = InitialApproximationsResponse {
+        left: 0.,
+        right: 1.,
+    };
+    let stream = spawn_device(move |sequence, _, output| {
+        let notification = Notification::InitialApproximationsChanged(changed);
+        write_frame(
+            |bytes| output.extend_from_slice(bytes),
+            MessageType::Notification,
+            UNSOLICITED_SEQUENCE,
+            &notification.to_bytes(),
+        );
+        respond(output, sequence, &APPROXIMATIONS.to_bytes());
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let approximations = device.initial_approximations().await.unwrap();
+    let Notification::InitialApproximationsChanged(notified) = device.notification().await.unwrap();
+
+    assert_eq!(approximations, APPROXIMATIONS);
+    assert_eq!(notified, changed);
+}
+
+#[tokio::test]
+async fn late_response_is_skipped() {
+    let stream = spawn_device(|sequence, _, output| {
+        // answer to the previous request arrives first
+        respond(output, sequence.wrapping_sub(1), &[0; PACKAGE_SIZE]);
+        respond(output, sequence, &APPROXIMATIONS.to_bytes());
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let approximations = device.initial_approximations().await.unwrap();
+
+    assert_eq!(approximations, APPROXIMATIONS);
+}
+
+#[tokio::test]
+async fn error_frame_rejects_request() {
+    let stream = spawn_device(|sequence, _, output| {
+        write_frame(
+            |bytes| output.extend_from_slice(bytes),
+            MessageType::Error,
+            sequence,
+            &DecodeError::UnknownMethod(7).to_bytes(),
+        );
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let result = device.compute_root(compute_root_payload()).await;
+
+    assert!(matches!(
+        result,
+        Err(ClientError::Rejected(Some(DecodeError::UnknownMethod(7))))
+    ));
+}
+
+#[tokio::test]
+async fn broken_frame_is_counted_and_skipped() {
+    let stream = spawn_device(|sequence, _, output| {
+        respond(output, sequence, &APPROXIMATIONS.to_bytes());
+        // checksum is corrupted
+        *output.last_mut().unwrap() ^= 0xff;
+        respond(output, sequence, &APPROXIMATIONS.to_bytes());
+    });
+    let mut device = Device::handshake(stream).await.unwrap();
+
+    let approximations = device.initial_approximations().await.unwrap();
+
+    assert_eq!(approximations, APPROXIMATIONS);
+    assert_eq!(device.dropped_frames(), 1);
+}
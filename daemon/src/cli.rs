@@ -0,0 +1,448 @@
+//! Headless commands for scripts. Make the same requests GUI does
+//! and print responses either as plain text or as JSON
+
+use std::error::Error;
+
+use clap::{Args, Subcommand, ValueEnum};
+use client::{discovery, Catalogue, Device, Trace};
+use protocol::device_info::DeviceInfo;
+use protocol::point::Point;
+use protocol::request::compute_method::{Method, SystemMethod};
+use protocol::request::payloads::{ComputeRootPayload, StoppingCriterion};
+use protocol::request::{EquationMode, EquationModeRaw, Selection, SingleEquation};
+use protocol::response::{
+    ComputeRootResponse, FunctionPointsResponse, InitialApproximationsResponse, MethodError,
+    TraceRecord,
+};
+use protocol::{TNumber, POINT_AMOUNT, PROTOCOL_VERSION};
+use serde_json::{json, Value};
+use solvers::{LEFT_BORDER, RIGHT_BORDER};
+
+use crate::config::PortSettings;
+use crate::serial_port_thread::Link;
+use crate::simulator;
+
+/// Method failed to find the root or device rejected the request
+const EXIT_REJECTED: i32 = 1;
+/// Arguments make no sense together
+const EXIT_USAGE: i32 = 2;
+/// Device is not reachable or does not understand host
+const EXIT_DEVICE_ERROR: i32 = 3;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List equations known to device
+    Equations,
+    /// Find root within bracket set on device
+    Solve {
+        #[command(flatten)]
+        target: Target,
+        #[arg(long, value_enum)]
+        method: MethodArgument,
+        #[arg(long, default_value_t = 1e-3)]
+        epsilon: TNumber,
+        #[arg(long, default_value_t = 1000)]
+        max_iterations: u16,
+        #[arg(long, value_enum, default_value_t = CriterionArgument::Step)]
+        criterion: CriterionArgument,
+        /// Print state of the method after each iteration
+        #[arg(long)]
+        trace: bool,
+    },
+    /// Print points GUI plots
+    Points {
+        #[command(flatten)]
+        target: Target,
+    },
+    /// Get or set initial approximations
+    Bracket {
+        #[command(subcommand)]
+        action: BracketAction,
+    },
+}
+
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+pub struct Target {
+    /// Index of single equation, see `equations`
+    #[arg(long)]
+    equation: Option<u8>,
+    /// Index of system of equations, see `equations`
+    #[arg(long)]
+    system: Option<u8>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BracketAction {
+    Get,
+    #[command(allow_negative_numbers = true)]
+    Set {
+        left: TNumber,
+        right: TNumber,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MethodArgument {
+    Chord,
+    Secant,
+    SimpleIteration,
+    Newton,
+    Bisection,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CriterionArgument {
+    Step,
+    Residual,
+    Both,
+}
+
+impl From<CriterionArgument> for StoppingCriterion {
+    fn from(value: CriterionArgument) -> Self {
+        match value {
+            CriterionArgument::Step => StoppingCriterion::Step,
+            CriterionArgument::Residual => StoppingCriterion::Residual,
+            CriterionArgument::Both => StoppingCriterion::Both,
+        }
+    }
+}
+
+/// Runs command to completion. Returns exit code
+pub fn run(link: Link, command: Command, json: bool) -> i32 {
+    if let Command::Solve { target, method, .. } = &command {
+        if let Err(error) = target.mode(*method) {
+            eprintln!("{}", error);
+            return EXIT_USAGE;
+        }
+    }
+
+    let runtime = tokio::runtime::Runtime::new().expect("runtime is created");
+    match runtime.block_on(execute(&link, command, json)) {
+        Ok(Outcome::Done) => 0,
+        Ok(Outcome::Rejected) => EXIT_REJECTED,
+        Ok(Outcome::Invalid) => EXIT_USAGE,
+        Err(error) => {
+            eprintln!("{}", error);
+            EXIT_DEVICE_ERROR
+        }
+    }
+}
+
+enum Outcome {
+    Done,
+    Rejected,
+    /// Arguments do not match device
+    Invalid,
+}
+
+async fn execute(link: &Link, command: Command, json: bool) -> Result<Outcome, Box<dyn Error>> {
+    let mut device = connect(link).await?;
+    match command {
+        Command::Equations => print_catalogue(&device.list_equations().await?, json),
+        Command::Solve {
+            target,
+            method,
+            epsilon,
+            max_iterations,
+            criterion,
+            trace,
+        } => {
+            if !is_listed(&mut device, &target).await? {
+                return Ok(Outcome::Invalid);
+            }
+
+            let mode = target.mode(method)?;
+            check_support(device.info(), mode, trace)?;
+            let payload = ComputeRootPayload {
+                epsilon,
+                mode,
+                max_iterations,
+                criterion: criterion.into(),
+            };
+            let Trace { records, result } = match trace {
+                true => device.compute_root_trace(payload).await?,
+                false => Trace {
+                    records: Vec::new(),
+                    result: device.compute_root(payload).await?,
+                },
+            };
+            print_solution(&records, &result, trace, json);
+            if result.is_err() {
+                return Ok(Outcome::Rejected);
+            }
+        }
+        Command::Points { target } => {
+            if !is_listed(&mut device, &target).await? {
+                return Ok(Outcome::Invalid);
+            }
+
+            let curves = device.function_points(target.selection()).await?;
+            print_points(&curves, json);
+        }
+        Command::Bracket {
+            action: BracketAction::Get,
+        } => print_bracket(device.initial_approximations().await?, json),
+        Command::Bracket {
+            action: BracketAction::Set { left, right },
+        } => {
+            // device keeps previous approximations if requested ones are out of range or order
+            let approximations = device.set_initial_approximations(left, right).await?;
+            print_bracket(approximations, json);
+            if approximations != (InitialApproximationsResponse { left, right }) {
+                eprintln!(
+                    "bracket has to be within [{}, {}) with left end below the right one",
+                    LEFT_BORDER, RIGHT_BORDER
+                );
+                return Ok(Outcome::Rejected);
+            }
+        }
+    }
+
+    Ok(Outcome::Done)
+}
+
+impl Target {
+    fn selection(&self) -> Selection {
+        match (self.equation, self.system) {
+            (Some(index), _) => Selection {
+                mode: EquationModeRaw::SingleEquation,
+                index,
+            },
+            (None, Some(index)) => Selection {
+                mode: EquationModeRaw::SystemOfEquations,
+                index,
+            },
+            (None, None) => unreachable!("either is required by clap"),
+        }
+    }
+
+    fn mode(&self, method: MethodArgument) -> Result<EquationMode, &'static str> {
+        let selection = self.selection();
+        let mode = match selection.mode {
+            EquationModeRaw::SingleEquation => EquationMode::Single(SingleEquation {
+                method: match method {
+                    MethodArgument::Chord => Method::Chord,
+                    MethodArgument::Secant => Method::Secant,
+                    MethodArgument::SimpleIteration => Method::SimpleIterationSingle,
+                    MethodArgument::Newton => Method::Newton,
+                    MethodArgument::Bisection => Method::Bisection,
+                },
+                equation_number: selection.index,
+            }),
+            EquationModeRaw::SystemOfEquations => EquationMode::SystemOfEquations {
+                method: match method {
+                    MethodArgument::SimpleIteration => SystemMethod::SimpleIteration,
+                    MethodArgument::Newton => SystemMethod::Newton,
+                    MethodArgument::Chord | MethodArgument::Secant | MethodArgument::Bisection => {
+                        return Err("systems are solved by simple-iteration or newton")
+                    }
+                },
+                system_number: selection.index,
+            },
+        };
+
+        Ok(mode)
+    }
+}
+
+/// Compatible device
+async fn connect(link: &Link) -> Result<Device, Box<dyn Error>> {
+    let device = match link {
+        Link::Simulator => Device::handshake(Box::new(simulator::spawn())).await?,
+        Link::SerialPort(PortSettings {
+            path: Some(path),
+            baud_rate,
+        }) => Device::connect(path, *baud_rate).await?,
+        Link::SerialPort(PortSettings {
+            path: None,
+            baud_rate,
+        }) => discover(*baud_rate).await?,
+    };
+
+    let info = device.info();
+    if !info.is_compatible() {
+        return Err(format!(
+            "incompatible firmware: protocol version {}, {} points. \
+             Expected protocol version {}, {} points",
+            info.version, info.point_amount, PROTOCOL_VERSION, POINT_AMOUNT
+        )
+        .into());
+    }
+
+    Ok(device)
+}
+
+/// Device rejects unknown indices as well, but this way user is pointed to `equations`
+async fn is_listed(device: &mut Device, target: &Target) -> Result<bool, Box<dyn Error>> {
+    let catalogue = device.list_equations().await?;
+    let Selection { mode, index } = target.selection();
+    let (kind, amount) = match mode {
+        EquationModeRaw::SingleEquation => ("equation", catalogue.single.len()),
+        EquationModeRaw::SystemOfEquations => ("system", catalogue.systems.len()),
+    };
+
+    if index as usize >= amount {
+        eprintln!("there is no {} {}, see `equations`", kind, index);
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+fn check_support(info: DeviceInfo, mode: EquationMode, trace: bool) -> Result<(), String> {
+    let (supported, method) = match mode {
+        EquationMode::Single(SingleEquation { method, .. }) => {
+            (info.supports_method(method), method.to_string())
+        }
+        EquationMode::SystemOfEquations { method, .. } => {
+            (info.supports_system_method(method), method.to_string())
+        }
+    };
+
+    if !supported {
+        return Err(format!("device does not support {} method", method));
+    }
+    if trace && !info.supports_trace() {
+        return Err("device does not support trace".to_owned());
+    }
+
+    Ok(())
+}
+
+/// Connects to the only port which responds with protocol signature
+async fn discover(baud_rate: u32) -> Result<Device, Box<dyn Error>> {
+    let mut responding: Vec<(String, Device)> = discovery::discover(baud_rate)
+        .await?
+        .into_iter()
+        .filter_map(|(candidate, device)| Some((candidate.path, device?)))
+        .collect();
+
+    match responding.len() {
+        0 => Err("no port responds with protocol signature".into()),
+        1 => Ok(responding.remove(0).1),
+        _ => {
+            let paths: Vec<String> = responding.into_iter().map(|(path, _)| path).collect();
+            Err(format!(
+                "several devices respond: {}. Pick one with --port",
+                paths.join(", ")
+            )
+            .into())
+        }
+    }
+}
+
+fn print_catalogue(catalogue: &Catalogue, json: bool) {
+    if json {
+        println!(
+            "{}",
+            json!({ "equations": catalogue.single, "systems": catalogue.systems })
+        );
+        return;
+    }
+
+    for (index, equation) in catalogue.single.iter().enumerate() {
+        println!("equation {}: {}", index, equation);
+    }
+    for (index, (first, second)) in catalogue.systems.iter().enumerate() {
+        println!("system {}: {}; {}", index, first, second);
+    }
+}
+
+/// Curves are separated by an empty line in plain text
+fn print_points(curves: &[FunctionPointsResponse], json: bool) {
+    if json {
+        let curves: Vec<Vec<[TNumber; 2]>> = curves
+            .iter()
+            .map(|curve| curve.0.iter().map(|point| [point.x, point.y]).collect())
+            .collect();
+        println!("{}", json!({ "curves": curves }));
+        return;
+    }
+
+    for (index, curve) in curves.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        for Point { x, y } in curve.0 {
+            println!("{} {}", x, y);
+        }
+    }
+}
+
+fn print_bracket(approximations: InitialApproximationsResponse, json: bool) {
+    let InitialApproximationsResponse { left, right } = approximations;
+    match json {
+        true => println!("{}", json!({ "left": left, "right": right })),
+        false => println!("left: {} right: {}", left, right),
+    }
+}
+
+fn print_solution(
+    records: &[TraceRecord],
+    result: &Result<ComputeRootResponse, MethodError>,
+    trace: bool,
+    json: bool,
+) {
+    if json {
+        let mut output = match result {
+            Ok(response) => json!({
+                "x": response.root.x,
+                "y": response.root.y,
+                "iterations": response.iterations,
+                "step": response.step,
+                "residual": response.residual,
+            }),
+            Err(error) => {
+                let suggestion = error
+                    .suggestion()
+                    .map(|bracket| json!({ "left": bracket.left, "right": bracket.right }));
+                json!({ "error": error.to_string(), "suggestion": suggestion })
+            }
+        };
+        if trace {
+            let records: Vec<Value> = records
+                .iter()
+                .map(|record| {
+                    json!({
+                        "x": record.point.x,
+                        "y": record.point.y,
+                        "step": record.step,
+                        "left": record.left,
+                        "right": record.right,
+                    })
+                })
+                .collect();
+            output["trace"] = records.into();
+        }
+        println!("{}", output);
+        return;
+    }
+
+    for (index, record) in records.iter().enumerate() {
+        print!(
+            "{}: x: {} y: {} step: {:e}",
+            index + 1,
+            record.point.x,
+            record.point.y,
+            record.step
+        );
+        // open methods do not keep a bracket
+        match record.left.is_nan() {
+            true => println!(),
+            false => println!(" left: {} right: {}", record.left, record.right),
+        }
+    }
+    match result {
+        Ok(ComputeRootResponse {
+            root: Point { x, y },
+            iterations,
+            step,
+            residual,
+        }) => println!(
+            "x: {} y: {} iterations: {} step: {:e} residual: {:e}",
+            x, y, iterations, step, residual
+        ),
+        Err(error) => eprintln!("{}", error),
+    }
+}
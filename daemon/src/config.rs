@@ -0,0 +1,89 @@
+//! How daemon reaches the device. Flags take precedence over config file
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use protocol::BAUD_RATE;
+use serde::Deserialize;
+
+use crate::cli::Command;
+use crate::serial_port_thread::Link;
+
+#[derive(Debug, Parser)]
+#[command(about = "Plots equations and solves them on arduino")]
+struct Arguments {
+    /// Serial port arduino is plugged in. Discovered if omitted
+    #[arg(long)]
+    port: Option<String>,
+    /// Has to match the one firmware is built with
+    #[arg(long)]
+    baud_rate: Option<u32>,
+    /// TOML file with `port` and `baud_rate`
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Run simulated device instead of connecting to arduino
+    #[arg(long)]
+    simulate: bool,
+    /// Print command output as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    /// Run a single command instead of opening the window
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    port: Option<String>,
+    baud_rate: Option<u32>,
+}
+
+/// Serial port to connect to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSettings {
+    /// `None` if port has to be discovered
+    pub path: Option<String>,
+    pub baud_rate: u32,
+}
+
+/// What daemon was asked to do
+pub struct Invocation {
+    pub link: Link,
+    /// `None` if window has to be opened
+    pub command: Option<Command>,
+    pub json: bool,
+}
+
+/// Parses command line. Exits on invalid flags
+pub fn invocation() -> Result<Invocation, Box<dyn Error>> {
+    let arguments = Arguments::parse();
+    Ok(Invocation {
+        link: link(&arguments)?,
+        command: arguments.command,
+        json: arguments.json,
+    })
+}
+
+fn link(arguments: &Arguments) -> Result<Link, Box<dyn Error>> {
+    if arguments.simulate {
+        return Ok(Link::Simulator);
+    }
+
+    let file = match &arguments.config {
+        Some(path) => {
+            read_config_file(path).map_err(|error| format!("{}: {}", path.display(), error))?
+        }
+        None => ConfigFile::default(),
+    };
+
+    Ok(Link::SerialPort(PortSettings {
+        path: arguments.port.clone().or(file.port),
+        baud_rate: arguments.baud_rate.or(file.baud_rate).unwrap_or(BAUD_RATE),
+    }))
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile, Box<dyn Error>> {
+    Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+}
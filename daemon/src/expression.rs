@@ -0,0 +1,267 @@
+use std::fmt::Display;
+
+use protocol::decode_error::DecodeError;
+use protocol::expression::{Instruction, Program};
+use protocol::TNumber;
+
+mod derivative;
+mod parser;
+mod simplify;
+
+/// Equation typed in by user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(TNumber),
+    X,
+    Negate(Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Call(Function, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Ln,
+    Exp,
+}
+
+impl Function {
+    const ALL: [Function; 4] = [Function::Sin, Function::Cos, Function::Ln, Function::Exp];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Ln => "ln",
+            Function::Exp => "exp",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|function| function.name() == name)
+    }
+}
+
+/// Position is a character index within the typed text
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    UnexpectedCharacter(usize, char),
+    InvalidNumber(usize),
+    UnexpectedToken(usize),
+    UnexpectedEnd,
+    UnknownIdentifier(usize, String),
+    /// Opening parenthesis has no pair
+    UnclosedParenthesis(usize),
+    /// Compiled program does not fit into device
+    TooLong,
+    /// Evaluation needs more stack than device has
+    TooNested,
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // users count columns from one
+        match self {
+            ExpressionError::UnexpectedCharacter(position, character) => {
+                write!(f, "Unexpected '{character}' at column {}", position + 1)
+            }
+            ExpressionError::InvalidNumber(position) => {
+                write!(f, "Invalid number at column {}", position + 1)
+            }
+            ExpressionError::UnexpectedToken(position) => {
+                write!(f, "Unexpected token at column {}", position + 1)
+            }
+            ExpressionError::UnexpectedEnd => write!(f, "Equation ends unexpectedly"),
+            ExpressionError::UnknownIdentifier(position, name) => {
+                write!(f, "Unknown name '{name}' at column {}", position + 1)
+            }
+            ExpressionError::UnclosedParenthesis(position) => {
+                write!(f, "Parenthesis at column {} is not closed", position + 1)
+            }
+            ExpressionError::TooLong => write!(f, "Equation is too long for the device"),
+            ExpressionError::TooNested => write!(f, "Equation is too nested for the device"),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, ExpressionError> {
+        parser::parse(text)
+    }
+
+    /// Simplified derivative with respect to `x`
+    pub fn derivative(&self) -> Self {
+        simplify::simplify(derivative::derivative(self))
+    }
+
+    fn depends_on_x(&self) -> bool {
+        match self {
+            Expression::Number(_) => false,
+            Expression::X => true,
+            Expression::Negate(operand) | Expression::Call(_, operand) => operand.depends_on_x(),
+            Expression::Binary(_, left, right) => left.depends_on_x() || right.depends_on_x(),
+        }
+    }
+
+    /// Bytecode for the device. Validated the same way device does
+    pub fn compile(&self) -> Result<Program, ExpressionError> {
+        let mut instructions = Vec::new();
+        self.emit(&mut instructions);
+
+        let mut bytes = Vec::with_capacity(Program::encoded_size(&instructions));
+        Program::encode(&instructions, |chunk| bytes.extend_from_slice(chunk));
+        Program::try_from_bytes(&bytes).map_err(|error| match error {
+            DecodeError::PayloadTooLong => ExpressionError::TooLong,
+            _ => ExpressionError::TooNested,
+        })
+    }
+
+    /// Operands go first, operation follows
+    fn emit(&self, instructions: &mut Vec<Instruction>) {
+        match self {
+            Expression::Number(value) => instructions.push(Instruction::Constant(*value)),
+            Expression::X => instructions.push(Instruction::X),
+            Expression::Negate(operand) => {
+                operand.emit(instructions);
+                instructions.push(Instruction::Negate);
+            }
+            Expression::Binary(operator, left, right) => {
+                left.emit(instructions);
+                right.emit(instructions);
+                instructions.push(match operator {
+                    BinaryOperator::Add => Instruction::Add,
+                    BinaryOperator::Subtract => Instruction::Subtract,
+                    BinaryOperator::Multiply => Instruction::Multiply,
+                    BinaryOperator::Divide => Instruction::Divide,
+                    BinaryOperator::Pow => Instruction::Pow,
+                });
+            }
+            Expression::Call(function, argument) => {
+                argument.emit(instructions);
+                instructions.push(match function {
+                    Function::Sin => Instruction::Sin,
+                    Function::Cos => Instruction::Cos,
+                    Function::Ln => Instruction::Ln,
+                    Function::Exp => Instruction::Exp,
+                });
+            }
+        }
+    }
+}
+
+impl Expression {
+    const SUM: u8 = 0;
+    const PRODUCT: u8 = 1;
+    const NEGATION: u8 = 2;
+    const POWER: u8 = 3;
+    const PRIMARY: u8 = 4;
+
+    /// Same levels as in the parser
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Number(value) if value.is_sign_negative() => Self::NEGATION,
+            Expression::Number(_) | Expression::X | Expression::Call(..) => Self::PRIMARY,
+            Expression::Negate(_) => Self::NEGATION,
+            Expression::Binary(BinaryOperator::Add | BinaryOperator::Subtract, ..) => Self::SUM,
+            Expression::Binary(BinaryOperator::Multiply | BinaryOperator::Divide, ..) => {
+                Self::PRODUCT
+            }
+            Expression::Binary(BinaryOperator::Pow, ..) => Self::POWER,
+        }
+    }
+
+    fn fmt_operand(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        is_parenthesized: bool,
+    ) -> std::fmt::Result {
+        match is_parenthesized {
+            true => write!(f, "({self})"),
+            false => write!(f, "{self}"),
+        }
+    }
+}
+
+/// Formula which parses back into the same expression. Parentheses are put only where needed
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{value}"),
+            Expression::X => write!(f, "x"),
+            Expression::Negate(operand) => {
+                write!(f, "-")?;
+                operand.fmt_operand(f, operand.precedence() < Self::NEGATION)
+            }
+            Expression::Binary(BinaryOperator::Pow, base, exponent) => {
+                // right associative
+                base.fmt_operand(f, base.precedence() < Self::PRIMARY)?;
+                write!(f, "^")?;
+                exponent.fmt_operand(f, exponent.precedence() < Self::NEGATION)
+            }
+            Expression::Binary(operator, left, right) => {
+                let symbol = match operator {
+                    BinaryOperator::Add => " + ",
+                    BinaryOperator::Subtract => " - ",
+                    BinaryOperator::Multiply => "*",
+                    BinaryOperator::Divide => "/",
+                    BinaryOperator::Pow => unreachable!("handled above"),
+                };
+                let precedence = self.precedence();
+                left.fmt_operand(f, left.precedence() < precedence)?;
+                write!(f, "{symbol}")?;
+                // left associative. Negative operand is parenthesized to not look like typo
+                let right_precedence = right.precedence();
+                right.fmt_operand(
+                    f,
+                    right_precedence <= precedence || right_precedence == Self::NEGATION,
+                )
+            }
+            Expression::Call(function, argument) => write!(f, "{}({argument})", function.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Expression {
+    /// Reference value in double precision. Tests tell expressions apart by their values
+    fn evaluate(&self, x: f64) -> f64 {
+        match self {
+            Expression::Number(value) => *value as f64,
+            Expression::X => x,
+            Expression::Negate(operand) => -operand.evaluate(x),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(x), right.evaluate(x));
+                match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide => left / right,
+                    BinaryOperator::Pow => left.powf(right),
+                }
+            }
+            Expression::Call(function, argument) => {
+                let argument = argument.evaluate(x);
+                match function {
+                    Function::Sin => argument.sin(),
+                    Function::Cos => argument.cos(),
+                    Function::Ln => argument.ln(),
+                    Function::Exp => argument.exp(),
+                }
+            }
+        }
+    }
+}
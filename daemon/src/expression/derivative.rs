@@ -0,0 +1,160 @@
+//! Symbolic differentiation with respect to `x`. Result is not simplified:
+//! rules are applied literally, so `x^2` turns into `2*x^(2 - 1)*1`
+
+use super::{BinaryOperator, Expression, Function};
+
+pub fn derivative(expression: &Expression) -> Expression {
+    use BinaryOperator::*;
+
+    match expression {
+        Expression::Number(_) => Expression::Number(0.),
+        Expression::X => Expression::Number(1.),
+        Expression::Negate(operand) => negate(derivative(operand)),
+        Expression::Binary(operator @ (Add | Subtract), left, right) => {
+            binary(*operator, derivative(left), derivative(right))
+        }
+        // (uv)' = u'v + uv'
+        Expression::Binary(Multiply, left, right) => binary(
+            Add,
+            binary(Multiply, derivative(left), right.as_ref().clone()),
+            binary(Multiply, left.as_ref().clone(), derivative(right)),
+        ),
+        // (u/v)' = (u'v - uv') / v^2
+        Expression::Binary(Divide, left, right) => binary(
+            Divide,
+            binary(
+                Subtract,
+                binary(Multiply, derivative(left), right.as_ref().clone()),
+                binary(Multiply, left.as_ref().clone(), derivative(right)),
+            ),
+            binary(Pow, right.as_ref().clone(), Expression::Number(2.)),
+        ),
+        Expression::Binary(Pow, base, exponent) => power(base, exponent),
+        Expression::Call(function, argument) => {
+            let outer = match function {
+                Function::Sin => call(Function::Cos, argument),
+                Function::Cos => negate(call(Function::Sin, argument)),
+                Function::Ln => binary(Divide, Expression::Number(1.), argument.as_ref().clone()),
+                Function::Exp => call(Function::Exp, argument),
+            };
+
+            // chain rule
+            binary(Multiply, outer, derivative(argument))
+        }
+    }
+}
+
+/// Constant exponent keeps the power rule, so negative bases are differentiated
+/// as well. Otherwise *u^v = exp(v ln u)* is differentiated, which requires *u > 0*
+fn power(base: &Expression, exponent: &Expression) -> Expression {
+    use BinaryOperator::*;
+
+    let base = base.clone();
+    let exponent = exponent.clone();
+    if !exponent.depends_on_x() {
+        // (u^c)' = c * u^(c - 1) * u'
+        let decremented = binary(Subtract, exponent.clone(), Expression::Number(1.));
+        let base_derivative = derivative(&base);
+        return binary(
+            Multiply,
+            binary(Multiply, exponent, binary(Pow, base, decremented)),
+            base_derivative,
+        );
+    }
+
+    // (u^v)' = u^v * (v' ln u + v u' / u)
+    let logarithm = call(Function::Ln, &base);
+    let rate = binary(
+        Add,
+        binary(Multiply, derivative(&exponent), logarithm),
+        binary(
+            Divide,
+            binary(Multiply, exponent.clone(), derivative(&base)),
+            base.clone(),
+        ),
+    );
+    binary(Multiply, binary(Pow, base, exponent), rate)
+}
+
+fn binary(operator: BinaryOperator, left: Expression, right: Expression) -> Expression {
+    Expression::Binary(operator, left.into(), right.into())
+}
+
+fn negate(operand: Expression) -> Expression {
+    Expression::Negate(operand.into())
+}
+
+fn call(function: Function, argument: &Expression) -> Expression {
+    Expression::Call(function, argument.clone().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f64 = 1e-5;
+
+    /// Compares symbolic derivative with the central difference at every point
+    fn assert_derivative(formula: &str, points: &[f64]) {
+        let function = Expression::parse(formula).unwrap();
+        let derived = derivative(&function);
+        for &x in points {
+            let expected =
+                (function.evaluate(x + STEP) - function.evaluate(x - STEP)) / (2. * STEP);
+            let actual = derived.evaluate(x);
+
+            assert!(
+                (actual - expected).abs() <= 1e-4 * expected.abs().max(1.),
+                "({})' at {} is {}, expected {}",
+                formula,
+                x,
+                actual,
+                expected
+            );
+        }
+    }
+
+    const POINTS: [f64; 5] = [-2.5, -1., 0.3, 1., 2.];
+    const POSITIVE_POINTS: [f64; 4] = [0.2, 0.7, 1.5, 3.];
+
+    #[test]
+    fn sum_and_negation() {
+        assert_derivative("-x + 3 - 2*x", &POINTS);
+    }
+
+    #[test]
+    fn product_rule() {
+        assert_derivative("x*sin(x)", &POINTS);
+    }
+
+    #[test]
+    fn quotient_rule() {
+        assert_derivative("cos(x)/(x^2 + 1)", &POINTS);
+    }
+
+    #[test]
+    fn chain_rule() {
+        assert_derivative("exp(sin(2*x))", &POINTS);
+        assert_derivative("cos(x^3 - x)", &POINTS);
+    }
+
+    #[test]
+    fn power_with_constant_exponent() {
+        assert_derivative("x^3", &POINTS);
+        assert_derivative("(x - 3)^2", &POINTS);
+        assert_derivative("x^-2", &POINTS);
+        assert_derivative("x^0.5", &POSITIVE_POINTS);
+    }
+
+    #[test]
+    fn power_with_variable_exponent() {
+        assert_derivative("x^x", &POSITIVE_POINTS);
+        assert_derivative("2^(x^2)", &POINTS);
+    }
+
+    #[test]
+    fn logarithm() {
+        assert_derivative("ln(x)", &POSITIVE_POINTS);
+        assert_derivative("ln(x^2 + 1)", &POINTS);
+    }
+}
@@ -0,0 +1,362 @@
+//! Recursive descent parser. Precedence from the lowest:
+//!
+//! ```text
+//! sum     = product (('+' | '-') product)*
+//! product = unary (('*' | '/') unary)*
+//! unary   = '-' unary | power
+//! power   = primary ('^' unary)?
+//! primary = number | 'x' | constant | function '(' sum ')' | '(' sum ')'
+//! ```
+//!
+//! Power is right associative and binds tighter than negation: `-x^2` is `-(x^2)`
+
+use protocol::TNumber;
+
+use super::{BinaryOperator, Expression, ExpressionError, Function};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Number(TNumber),
+    Identifier(&'a str),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+pub fn parse(text: &str) -> Result<Expression, ExpressionError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        next: 0,
+    };
+
+    let expression = parser.sum()?;
+    match parser.peek() {
+        None => Ok(expression),
+        Some((position, _)) => Err(ExpressionError::UnexpectedToken(position)),
+    }
+}
+
+/// Tokens along with their positions
+fn tokenize(text: &str) -> Result<Vec<(usize, Token<'_>)>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut characters = text.char_indices().enumerate().peekable();
+    while let Some((position, (start, character))) = characters.next() {
+        let token = match character {
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '^' => Token::Caret,
+            '(' => Token::OpenParenthesis,
+            ')' => Token::CloseParenthesis,
+            character if character.is_whitespace() => continue,
+            character if character.is_ascii_digit() || character == '.' => {
+                let mut end = start + character.len_utf8();
+                while let Some(&(_, (index, next))) = characters.peek() {
+                    if !(next.is_ascii_digit() || next == '.') {
+                        break;
+                    }
+                    end = index + next.len_utf8();
+                    characters.next();
+                }
+
+                let number = text[start..end]
+                    .parse()
+                    .map_err(|_| ExpressionError::InvalidNumber(position))?;
+                Token::Number(number)
+            }
+            character if character.is_alphabetic() => {
+                let mut end = start + character.len_utf8();
+                while let Some(&(_, (index, next))) = characters.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    end = index + next.len_utf8();
+                    characters.next();
+                }
+
+                Token::Identifier(&text[start..end])
+            }
+            _ => return Err(ExpressionError::UnexpectedCharacter(position, character)),
+        };
+
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    /// Index of the token which is not consumed yet
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<(usize, Token<'a>)> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn advance(&mut self) -> Result<(usize, Token<'a>), ExpressionError> {
+        let token = self.peek().ok_or(ExpressionError::UnexpectedEnd)?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    /// Consumes token if it is one of the `operators`
+    fn operator(&mut self, operators: &[(Token, BinaryOperator)]) -> Option<BinaryOperator> {
+        let (_, token) = self.peek()?;
+        let (_, operator) = operators.iter().find(|(expected, _)| *expected == token)?;
+        self.next += 1;
+        Some(*operator)
+    }
+
+    fn sum(&mut self) -> Result<Expression, ExpressionError> {
+        const OPERATORS: [(Token, BinaryOperator); 2] = [
+            (Token::Plus, BinaryOperator::Add),
+            (Token::Minus, BinaryOperator::Subtract),
+        ];
+
+        let mut expression = self.product()?;
+        while let Some(operator) = self.operator(&OPERATORS) {
+            expression = Expression::Binary(operator, expression.into(), self.product()?.into());
+        }
+
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<Expression, ExpressionError> {
+        const OPERATORS: [(Token, BinaryOperator); 2] = [
+            (Token::Star, BinaryOperator::Multiply),
+            (Token::Slash, BinaryOperator::Divide),
+        ];
+
+        let mut expression = self.unary()?;
+        while let Some(operator) = self.operator(&OPERATORS) {
+            expression = Expression::Binary(operator, expression.into(), self.unary()?.into());
+        }
+
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        if let Some((_, Token::Minus)) = self.peek() {
+            self.next += 1;
+            return Ok(Expression::Negate(self.unary()?.into()));
+        }
+
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expression, ExpressionError> {
+        let base = self.primary()?;
+        match self.operator(&[(Token::Caret, BinaryOperator::Pow)]) {
+            Some(operator) => Ok(Expression::Binary(
+                operator,
+                base.into(),
+                self.unary()?.into(),
+            )),
+            None => Ok(base),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        let (position, token) = self.advance()?;
+        match token {
+            Token::Number(value) => Ok(Expression::Number(value)),
+            Token::OpenParenthesis => self.parenthesized(position),
+            Token::Identifier("x") => Ok(Expression::X),
+            Token::Identifier("pi") => Ok(Expression::Number(std::f32::consts::PI)),
+            Token::Identifier("e") => Ok(Expression::Number(std::f32::consts::E)),
+            Token::Identifier(name) => {
+                let function = Function::from_name(name)
+                    .ok_or_else(|| ExpressionError::UnknownIdentifier(position, name.to_owned()))?;
+                match self.advance()? {
+                    (position, Token::OpenParenthesis) => Ok(Expression::Call(
+                        function,
+                        self.parenthesized(position)?.into(),
+                    )),
+                    (position, _) => Err(ExpressionError::UnexpectedToken(position)),
+                }
+            }
+            _ => Err(ExpressionError::UnexpectedToken(position)),
+        }
+    }
+
+    /// Rest of the expression after the opening parenthesis at `position`
+    fn parenthesized(&mut self, position: usize) -> Result<Expression, ExpressionError> {
+        let expression = self.sum()?;
+        match self.peek() {
+            Some((_, Token::CloseParenthesis)) => {
+                self.next += 1;
+                Ok(expression)
+            }
+            Some((position, _)) => Err(ExpressionError::UnexpectedToken(position)),
+            None => Err(ExpressionError::UnclosedParenthesis(position)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: TNumber) -> Expression {
+        Expression::Number(value)
+    }
+
+    fn binary(operator: BinaryOperator, left: Expression, right: Expression) -> Expression {
+        Expression::Binary(operator, left.into(), right.into())
+    }
+
+    fn negate(operand: Expression) -> Expression {
+        Expression::Negate(operand.into())
+    }
+
+    #[test]
+    fn product_binds_tighter_than_sum() {
+        let expected = binary(
+            BinaryOperator::Add,
+            number(1.),
+            binary(BinaryOperator::Multiply, number(2.), Expression::X),
+        );
+
+        assert_eq!(parse("1 + 2*x"), Ok(expected));
+    }
+
+    #[test]
+    fn subtraction_is_left_associative() {
+        let expected = binary(
+            BinaryOperator::Subtract,
+            binary(BinaryOperator::Subtract, Expression::X, number(1.)),
+            number(2.),
+        );
+
+        assert_eq!(parse("x - 1 - 2"), Ok(expected));
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        let expected = binary(
+            BinaryOperator::Pow,
+            number(2.),
+            binary(BinaryOperator::Pow, number(3.), number(2.)),
+        );
+
+        assert_eq!(parse("2^3^2"), Ok(expected));
+    }
+
+    #[test]
+    fn power_binds_tighter_than_negation() {
+        let squared = binary(BinaryOperator::Pow, Expression::X, number(2.));
+
+        assert_eq!(parse("-x^2"), Ok(negate(squared)));
+    }
+
+    #[test]
+    fn exponent_may_be_negated() {
+        let expected = binary(BinaryOperator::Pow, Expression::X, negate(number(2.)));
+
+        assert_eq!(parse("x^-2"), Ok(expected));
+    }
+
+    #[test]
+    fn negation_repeats() {
+        assert_eq!(parse("--x"), Ok(negate(negate(Expression::X))));
+    }
+
+    #[test]
+    fn functions_and_constants_are_recognized() {
+        let expected = Expression::Call(
+            Function::Sin,
+            binary(
+                BinaryOperator::Multiply,
+                number(std::f32::consts::PI),
+                Expression::X,
+            )
+            .into(),
+        );
+
+        assert_eq!(parse("sin(pi * x)"), Ok(expected));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let cases = [
+            ("x + $", ExpressionError::UnexpectedCharacter(4, '$')),
+            ("1..2 + x", ExpressionError::InvalidNumber(0)),
+            ("x x", ExpressionError::UnexpectedToken(2)),
+            ("sin x", ExpressionError::UnexpectedToken(4)),
+            ("x + )", ExpressionError::UnexpectedToken(4)),
+            ("x +", ExpressionError::UnexpectedEnd),
+            (
+                "2*tan(x)",
+                ExpressionError::UnknownIdentifier(2, "tan".to_owned()),
+            ),
+            ("1 + (x*(2", ExpressionError::UnclosedParenthesis(7)),
+        ];
+
+        for (text, error) in cases {
+            assert_eq!(parse(text), Err(error), "{}", text);
+        }
+    }
+
+    #[test]
+    fn position_counts_characters_rather_than_bytes() {
+        // no-break space takes two bytes
+        let result = parse("\u{a0}x $");
+
+        assert_eq!(result, Err(ExpressionError::UnexpectedCharacter(3, '$')));
+    }
+
+    #[test]
+    fn display_parses_back() {
+        let formulas = [
+            "-x^2",
+            "(-x)^2",
+            "2^3^2",
+            "(2^3)^2",
+            "x^-2",
+            "x - (1 - x)",
+            "x - 1 - x",
+            "x/(2*x)",
+            "x/2*x",
+            "-(x + 1)*3",
+            "x*-x",
+            "sin(-x)^2 + cos(x^2)",
+            "exp(ln(x)/2)",
+        ];
+
+        for formula in formulas {
+            let expression = parse(formula).unwrap();
+            let displayed = expression.to_string();
+
+            assert_eq!(
+                parse(&displayed),
+                Ok(expression),
+                "{} -> {}",
+                formula,
+                displayed
+            );
+        }
+    }
+
+    #[test]
+    fn display_puts_parentheses_only_where_needed() {
+        let cases = [
+            ("((x))", "x"),
+            ("(x*2) + 1", "x*2 + 1"),
+            ("x - (1 + x)", "x - (1 + x)"),
+            ("(x^2)^3", "(x^2)^3"),
+            ("x^(2^3)", "x^2^3"),
+        ];
+
+        for (formula, displayed) in cases {
+            assert_eq!(parse(formula).unwrap().to_string(), displayed);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Folds constants and drops neutral operands. Good enough to make derivatives
+//! readable, far from a computer algebra system
+
+use protocol::TNumber;
+
+use super::{BinaryOperator, Expression, Function};
+
+pub fn simplify(expression: Expression) -> Expression {
+    match expression {
+        Expression::Number(_) | Expression::X => expression,
+        Expression::Negate(operand) => negate(simplify(*operand)),
+        Expression::Binary(operator, left, right) => {
+            binary(operator, simplify(*left), simplify(*right))
+        }
+        Expression::Call(function, argument) => call(function, simplify(*argument)),
+    }
+}
+
+/// Operands below are simplified already
+fn negate(operand: Expression) -> Expression {
+    match operand {
+        Expression::Number(value) => Expression::Number(-value),
+        Expression::Negate(operand) => *operand,
+        // `-2*x` rather than `-(2*x)`
+        Expression::Binary(
+            operator @ (BinaryOperator::Multiply | BinaryOperator::Divide),
+            left,
+            right,
+        ) if matches!(*left, Expression::Number(_)) => {
+            Expression::Binary(operator, negate(*left).into(), right)
+        }
+        operand => Expression::Negate(operand.into()),
+    }
+}
+
+fn binary(operator: BinaryOperator, left: Expression, right: Expression) -> Expression {
+    use BinaryOperator::*;
+    use Expression::{Negate, Number};
+
+    if let (Number(left), Number(right)) = (&left, &right) {
+        let value = evaluate(operator, *left, *right);
+        // division by zero and alike are left for device to evaluate
+        if value.is_finite() {
+            return Number(value);
+        }
+    }
+
+    match (operator, left, right) {
+        (Add, Number(0.), operand) | (Add | Subtract, operand, Number(0.)) => operand,
+        (Subtract, Number(0.), operand) => negate(operand),
+        (Add, left, right) if is_negative(&right) => binary(Subtract, left, negate(right)),
+        (Add, left, right) if is_negative(&left) => binary(Subtract, right, negate(left)),
+        (Subtract, left, right) if is_negative(&right) => binary(Add, left, negate(right)),
+        (Subtract, left, right) if left == right => Number(0.),
+
+        (Multiply, Number(0.), _) | (Multiply, _, Number(0.)) => Number(0.),
+        (Multiply, Number(1.), operand) | (Multiply | Divide, operand, Number(1.)) => operand,
+        (Divide, Number(0.), _) => Number(0.),
+        (Divide, left, right) if left == right => Number(1.),
+        (operator @ (Multiply | Divide), Negate(left), right) => {
+            negate(binary(operator, *left, right))
+        }
+        (operator @ (Multiply | Divide), left, Negate(right)) => {
+            negate(binary(operator, left, *right))
+        }
+        // constant goes first: `2*x` rather than `x*2`
+        (Multiply, left, Number(value)) => binary(Multiply, Number(value), left),
+        (Multiply, Number(outer), Expression::Binary(Multiply, inner, operand))
+            if matches!(*inner, Number(_)) =>
+        {
+            let Number(inner) = *inner else {
+                unreachable!("matched above")
+            };
+            binary(Multiply, Number(outer * inner), *operand)
+        }
+
+        (Pow, operand, Number(1.)) => operand,
+        (Pow, _, Number(0.)) => Number(1.),
+        (Pow, Number(1.), _) => Number(1.),
+        (Pow, Expression::Binary(Pow, base, inner), Number(outer))
+            if matches!(*inner, Number(_)) =>
+        {
+            let Number(inner) = *inner else {
+                unreachable!("matched above")
+            };
+            binary(Pow, *base, Number(inner * outer))
+        }
+
+        (operator, left, right) => Expression::Binary(operator, left.into(), right.into()),
+    }
+}
+
+/// Starts with minus once printed: negation makes it shorter
+fn is_negative(expression: &Expression) -> bool {
+    match expression {
+        Expression::Number(value) => *value < 0.,
+        Expression::Negate(_) => true,
+        Expression::Binary(BinaryOperator::Multiply | BinaryOperator::Divide, left, _) => {
+            matches!(**left, Expression::Number(value) if value < 0.)
+        }
+        _ => false,
+    }
+}
+
+fn call(function: Function, argument: Expression) -> Expression {
+    if let Expression::Number(value) = argument {
+        let value = match function {
+            Function::Sin => value.sin(),
+            Function::Cos => value.cos(),
+            Function::Ln => value.ln(),
+            Function::Exp => value.exp(),
+        };
+        if value.is_finite() {
+            return Expression::Number(value);
+        }
+    }
+
+    Expression::Call(function, argument.into())
+}
+
+fn evaluate(operator: BinaryOperator, left: TNumber, right: TNumber) -> TNumber {
+    match operator {
+        BinaryOperator::Add => left + right,
+        BinaryOperator::Subtract => left - right,
+        BinaryOperator::Multiply => left * right,
+        BinaryOperator::Divide => left / right,
+        BinaryOperator::Pow => left.powf(right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simplified(formula: &str) -> String {
+        simplify(Expression::parse(formula).unwrap()).to_string()
+    }
+
+    fn derived(formula: &str) -> String {
+        Expression::parse(formula).unwrap().derivative().to_string()
+    }
+
+    #[test]
+    fn constants_are_folded() {
+        assert_eq!(simplified("2*3 + x"), "6 + x");
+        assert_eq!(simplified("x^(4/2)"), "x^2");
+        assert_eq!(simplified("cos(0)*x"), "x");
+    }
+
+    #[test]
+    fn neutral_operands_are_dropped() {
+        assert_eq!(simplified("0 + x*1 - 0"), "x");
+        assert_eq!(simplified("x^1 / 1"), "x");
+        assert_eq!(simplified("0*sin(x) + 0/x"), "0");
+        assert_eq!(simplified("x^0"), "1");
+    }
+
+    #[test]
+    fn equal_operands_cancel() {
+        assert_eq!(simplified("sin(x) - sin(x)"), "0");
+        assert_eq!(simplified("(x + 1)/(x + 1)"), "1");
+    }
+
+    #[test]
+    fn negation_is_pulled_out() {
+        assert_eq!(simplified("--x"), "x");
+        assert_eq!(simplified("x + -2*x"), "x - 2*x");
+        assert_eq!(simplified("-x*x"), "-(x*x)");
+        assert_eq!(simplified("-(2*x)"), "-2*x");
+    }
+
+    #[test]
+    fn constant_factors_are_merged() {
+        assert_eq!(simplified("x*2"), "2*x");
+        assert_eq!(simplified("3*(2*x)"), "6*x");
+        assert_eq!(simplified("(x^2)^3"), "x^6");
+    }
+
+    #[test]
+    fn invalid_constant_is_left_for_device() {
+        assert_eq!(simplified("1/0 + x"), "1/0 + x");
+        assert_eq!(simplified("ln(0 - 1)"), "ln(-1)");
+    }
+
+    #[test]
+    fn derivatives_read_naturally() {
+        assert_eq!(derived("x^2"), "2*x");
+        assert_eq!(derived("x^3"), "3*x^2");
+        assert_eq!(derived("sin(x)"), "cos(x)");
+        assert_eq!(derived("cos(x)"), "-sin(x)");
+        assert_eq!(derived("ln(x)"), "1/x");
+        assert_eq!(derived("3*x - 5"), "3");
+    }
+
+    #[test]
+    fn value_is_kept() {
+        let formulas = [
+            "x*sin(x)",
+            "cos(x)/(x^2 + 1)",
+            "exp(sin(2*x))",
+            "(x - 3)^3",
+            "x^x",
+            "ln(x^2 + 1)",
+        ];
+
+        for formula in formulas {
+            let expression = Expression::parse(formula).unwrap();
+            let derivative = super::super::derivative::derivative(&expression);
+            let simplified = simplify(derivative.clone());
+            for x in [0.3, 1., 2.5] {
+                let (expected, actual) = (derivative.evaluate(x), simplified.evaluate(x));
+
+                assert!(
+                    (actual - expected).abs() <= 1e-4 * expected.abs().max(1.),
+                    "{} at {}: {} != {}",
+                    simplified,
+                    x,
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+}
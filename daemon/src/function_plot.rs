@@ -1,6 +1,10 @@
 use std::{cell::RefCell, ops::Range, result::Result};
 
-use iced::Element;
+use client::Catalogue;
+use iced::event::Status;
+use iced::mouse::{self, Cursor, Interaction};
+use iced::widget::canvas::Event;
+use iced::{Element, Rectangle};
 use plotters::{
     chart::ChartContext,
     coord::types::RangedCoordf32,
@@ -14,22 +18,46 @@ use protocol::{
     request::{EquationModeRaw, RequestPackage, Selection},
     response::{
         ComputeRootResponse, FunctionPointsResponse, InitialApproximationsResponse, MethodError,
-        ResponsePackage,
+        ResponsePackage, TraceRecord,
     },
     TNumber,
 };
+use solvers::{LEFT_BORDER, POINT_INTERVAL_LENGTH, RIGHT_BORDER};
 
 use crate::UIMessage;
 
+/// Iterations of the last computed root.
+#[derive(Debug, Clone, Default)]
+pub struct IterationTrace {
+    pub records: Vec<TraceRecord>,
+    /// Records are collected here until root is received.
+    /// This way trace of previous computation is displayed
+    /// until the new one is complete
+    pending: Vec<TraceRecord>,
+}
+
+impl IterationTrace {
+    fn push(&mut self, record: TraceRecord) {
+        self.pending.push(record);
+    }
+
+    /// Called when computation is finished
+    fn complete(&mut self) {
+        self.records = std::mem::take(&mut self.pending);
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct EquationPlot {
     pub computed_root: Option<Result<ComputeRootResponse, MethodError>>,
+    /// Result per interval with isolated root. Replaces `computed_root` while set
+    pub all_roots: Option<Vec<Result<ComputeRootResponse, MethodError>>>,
     pub function_points: Option<FunctionPointsResponse>,
+    pub trace: IterationTrace,
 }
 
 impl EquationPlot {
     fn is_loading(&self) -> bool {
-        /* self.computed_root.is_none() || */
         self.function_points.is_none()
     }
 }
@@ -39,6 +67,7 @@ pub struct SystemOfEquationsPlot {
     pub computed_root: Option<Result<ComputeRootResponse, MethodError>>,
     pub first_function_points: Option<FunctionPointsResponse>,
     pub second_function_points: Option<FunctionPointsResponse>,
+    pub trace: IterationTrace,
 }
 
 impl SystemOfEquationsPlot {
@@ -61,6 +90,8 @@ pub struct FunctionPlot {
     /// area requires to build dummy chart with dummy coordinates
     /// first. And only then build actual chart with proper coordinates
     aspect_ratio: RefCell<f64>,
+    /// Maps cursor position to x coordinate. Known once chart is drawn
+    x_axis: RefCell<Option<XAxis>>,
 
     // local state
     // using structs instead of enum to preserve state.
@@ -76,6 +107,45 @@ struct FunctionPlotState<'a> {
     state: &'a FunctionPlot,
 }
 
+/// Horizontal axis of the drawn chart
+#[derive(Debug, Clone)]
+struct XAxis {
+    /// Plotting area relative to the chart widget
+    pixels: Range<i32>,
+    /// Coordinates at plotting area borders
+    values: Range<TNumber>,
+    /// Approximations are kept within plotted function
+    limits: Range<TNumber>,
+}
+
+impl XAxis {
+    fn value(&self, pixel: f32) -> TNumber {
+        let share =
+            (pixel - self.pixels.start as f32) / (self.pixels.end - self.pixels.start) as f32;
+        let value = self.values.start + share * (self.values.end - self.values.start);
+        value.clamp(self.limits.start, self.limits.end)
+    }
+
+    fn pixel(&self, value: TNumber) -> f32 {
+        let share = (value - self.values.start) / (self.values.end - self.values.start);
+        self.pixels.start as f32 + share * (self.pixels.end - self.pixels.start) as f32
+    }
+}
+
+/// Vertical line of initial approximations
+#[derive(Debug, Clone, Copy)]
+enum ApproximationLine {
+    Left,
+    Right,
+}
+
+/// Line which is dragged with mouse and where it is dragged to.
+/// Device is asked to move the line once mouse button is released
+#[derive(Debug, Default)]
+struct DragState {
+    dragged: Option<(ApproximationLine, TNumber)>,
+}
+
 impl FunctionPlot {
     pub fn has_intial_approximations_changed(&self, response: &ResponsePackage) -> bool {
         if let ResponsePackage::InitialApproximations(new) = response {
@@ -88,19 +158,27 @@ impl FunctionPlot {
     }
 
     pub fn new() -> Self {
-        const EQUATION_AMOUNT_WITH_RESERVE: usize = 10;
-
-        let mut single = Vec::new();
-        single.resize(EQUATION_AMOUNT_WITH_RESERVE, Default::default());
-
-        let mut system = Vec::new();
-        system.resize(EQUATION_AMOUNT_WITH_RESERVE, Default::default());
-
         Self {
             initial_approximations: Default::default(),
             aspect_ratio: RefCell::new(f64::NAN),
-            single,
-            system,
+            x_axis: RefCell::new(None),
+            single: Vec::new(),
+            system: Vec::new(),
+        }
+    }
+
+    /// Make room for every equation in the catalogue
+    pub fn set_catalogue(&mut self, catalogue: &Catalogue) {
+        self.single
+            .resize(catalogue.single.len(), Default::default());
+        self.system
+            .resize(catalogue.systems.len(), Default::default());
+    }
+
+    /// Equation in the slot is replaced. Previous points and root do not belong to it
+    pub fn reset_single(&mut self, index: u8) {
+        if let Some(single) = self.single.get_mut(index as usize) {
+            *single = Default::default();
         }
     }
 
@@ -112,16 +190,24 @@ impl FunctionPlot {
 
         let selection = match request {
             RequestPackage::FunctionPoints { payload } => payload,
-            RequestPackage::ComputeRoot { payload } => &payload.mode.clone().into(),
+            RequestPackage::ComputeRoot { payload }
+            | RequestPackage::ComputeRootTrace { payload } => &payload.mode.into(),
             _ => unreachable!(),
         };
 
         match selection.mode {
             EquationModeRaw::SingleEquation => {
-                let single = &mut self.single[selection.index as usize];
+                let Some(single) = self.single.get_mut(selection.index as usize) else {
+                    return;
+                };
 
                 match response {
-                    ResponsePackage::ComputeRoot(response) => single.computed_root = Some(response),
+                    ResponsePackage::ComputeRoot(response) => {
+                        single.computed_root = Some(response);
+                        single.all_roots = None;
+                        single.trace.complete();
+                    }
+                    ResponsePackage::ComputeRootTrace(record) => single.trace.push(record),
                     ResponsePackage::FunctionPoints(response) => {
                         single.function_points = Some(response)
                     }
@@ -129,10 +215,16 @@ impl FunctionPlot {
                 }
             }
             EquationModeRaw::SystemOfEquations => {
-                let system = &mut self.system[selection.index as usize];
+                let Some(system) = self.system.get_mut(selection.index as usize) else {
+                    return;
+                };
 
                 match response {
-                    ResponsePackage::ComputeRoot(response) => system.computed_root = Some(response),
+                    ResponsePackage::ComputeRoot(response) => {
+                        system.computed_root = Some(response);
+                        system.trace.complete();
+                    }
+                    ResponsePackage::ComputeRootTrace(record) => system.trace.push(record),
                     ResponsePackage::FunctionPoints(response) => {
                         system.first_function_points = Some(response)
                     }
@@ -148,12 +240,14 @@ impl FunctionPlot {
     pub fn view(&self, selection: Selection) -> Element<UIMessage> {
         let is_loading = self.initial_approximations.is_none()
             || match selection.mode {
-                EquationModeRaw::SingleEquation => {
-                    self.single[selection.index as usize].is_loading()
-                }
-                EquationModeRaw::SystemOfEquations => {
-                    self.system[selection.index as usize].is_loading()
-                }
+                EquationModeRaw::SingleEquation => self
+                    .single
+                    .get(selection.index as usize)
+                    .is_none_or(EquationPlot::is_loading),
+                EquationModeRaw::SystemOfEquations => self
+                    .system
+                    .get(selection.index as usize)
+                    .is_none_or(SystemOfEquationsPlot::is_loading),
             };
 
         if is_loading {
@@ -173,22 +267,99 @@ impl FunctionPlot {
     ) -> Option<Result<ComputeRootResponse, MethodError>> {
         let index = selection.index as usize;
         match selection.mode {
-            EquationModeRaw::SingleEquation => self.single[index].computed_root,
-            EquationModeRaw::SystemOfEquations => self.system[index].computed_root,
+            EquationModeRaw::SingleEquation => self.single.get(index)?.computed_root,
+            EquationModeRaw::SystemOfEquations => self.system.get(index)?.computed_root,
+        }
+    }
+
+    pub(crate) fn set_all_roots(
+        &mut self,
+        selection: Selection,
+        roots: Vec<Result<ComputeRootResponse, MethodError>>,
+    ) {
+        // systems are always solved one root at a time
+        if selection.mode != EquationModeRaw::SingleEquation {
+            return;
+        }
+
+        if let Some(single) = self.single.get_mut(selection.index as usize) {
+            single.computed_root = None;
+            single.all_roots = Some(roots);
+        }
+    }
+
+    pub(crate) fn get_all_roots(
+        &self,
+        selection: Selection,
+    ) -> Option<&[Result<ComputeRootResponse, MethodError>]> {
+        match selection.mode {
+            EquationModeRaw::SingleEquation => self
+                .single
+                .get(selection.index as usize)?
+                .all_roots
+                .as_deref(),
+            EquationModeRaw::SystemOfEquations => None,
+        }
+    }
+
+    pub(crate) fn get_trace(&self, selection: Selection) -> &[TraceRecord] {
+        let index = selection.index as usize;
+        match selection.mode {
+            EquationModeRaw::SingleEquation => self.single.get(index).map(|plot| &plot.trace),
+            EquationModeRaw::SystemOfEquations => self.system.get(index).map(|plot| &plot.trace),
         }
+        .map_or(&[], |trace| &trace.records)
     }
 }
 
 const MARGINS: i32 = 10;
 const COORD_MARGIN_PERCENT: TNumber = 0.05;
+/// Approximation line is grabbed within this distance
+const GRAB_DISTANCE_PIXELS: f32 = 8.;
+
+impl<'a> FunctionPlotState<'a> {
+    /// Where `line` dragged to `x` ends up. Lines stay within the range
+    /// device accepts and at least a grid cell apart, so they never cross
+    fn drag_target(&self, line: ApproximationLine, x: TNumber) -> TNumber {
+        let Some(approximations) = self.state.initial_approximations else {
+            return x;
+        };
+
+        match line {
+            ApproximationLine::Left => x
+                .min(approximations.right - POINT_INTERVAL_LENGTH)
+                .max(LEFT_BORDER),
+            ApproximationLine::Right => x
+                .max(approximations.left + POINT_INTERVAL_LENGTH)
+                .min(RIGHT_BORDER - POINT_INTERVAL_LENGTH),
+        }
+    }
+
+    /// Approximation line under the cursor
+    fn line_at(&self, pixel: f32) -> Option<ApproximationLine> {
+        let x_axis = self.state.x_axis.borrow();
+        let x_axis = x_axis.as_ref()?;
+        let approximations = self.state.initial_approximations?;
+
+        [
+            (ApproximationLine::Left, approximations.left),
+            (ApproximationLine::Right, approximations.right),
+        ]
+        .into_iter()
+        .map(|(line, x)| (line, (x_axis.pixel(x) - pixel).abs()))
+        .filter(|&(_, distance)| distance <= GRAB_DISTANCE_PIXELS)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(line, _)| line)
+    }
+}
 
 impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
     // internal state, part of stateless widgets model
-    type State = ();
+    type State = DragState;
 
     fn build_chart<DB: plotters::prelude::DrawingBackend>(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         builder: ChartBuilder<DB>,
     ) {
         use plotters::prelude::*;
@@ -197,13 +368,14 @@ impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
 
         let aspect_ratio = self.state.aspect_ratio.replace_with(|old| *old);
 
-        let (function_points, second, computed_root) = match self.selection.mode {
+        let (function_points, second, computed_root, all_roots) = match self.selection.mode {
             EquationModeRaw::SingleEquation => {
                 let equation = &self.state.single[self.selection.index as usize];
                 (
                     &equation.function_points.unwrap(),
                     None,
                     equation.computed_root,
+                    equation.all_roots.as_deref(),
                 )
             }
             EquationModeRaw::SystemOfEquations => {
@@ -212,6 +384,7 @@ impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
                     &system.first_function_points.unwrap(),
                     Some(system.second_function_points),
                     system.computed_root,
+                    None,
                 )
             }
         };
@@ -223,11 +396,26 @@ impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
 
         // expects should never trigger as effective iced backend
         // is not capable of producing errors
-        let initial_approximations = self.state.initial_approximations.expect(
+        let mut initial_approximations = self.state.initial_approximations.expect(
             "Chart should be not drawn if initial approximations have not been received yet!",
         );
+        match state.dragged {
+            Some((ApproximationLine::Left, x)) => initial_approximations.left = x,
+            Some((ApproximationLine::Right, x)) => initial_approximations.right = x,
+            None => (),
+        }
+
+        let mut chart = configure_chart(builder, x_range.clone(), y_range);
+        self.state.x_axis.replace(Some(XAxis {
+            pixels: chart.plotting_area().get_pixel_range().0,
+            values: chart.x_range(),
+            limits: x_range,
+        }));
 
-        let mut chart = configure_chart(builder, x_range, y_range);
+        // drawn first, so it does not cover anything
+        if let Some(suggestion) = computed_root.and_then(|root| root.err()?.suggestion()) {
+            draw_band(&mut chart, suggestion.left, suggestion.right);
+        }
 
         draw_vertical_line(&mut chart, initial_approximations.left);
         draw_vertical_line(&mut chart, initial_approximations.right);
@@ -256,6 +444,19 @@ impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
                 ))
                 .expect("could draw root point");
         }
+
+        if let Some(all_roots) = all_roots {
+            chart
+                .draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
+                    all_roots
+                        .iter()
+                        .flatten()
+                        .map(|response| (response.root.x, response.root.y)),
+                    POINT_SIZE,
+                    RGBColor(0xb8, 0xbb, 0x26).filled(),
+                ))
+                .expect("could draw root points");
+        }
     }
 
     fn draw_chart<DB: DrawingBackend>(
@@ -270,6 +471,83 @@ impl<'a> Chart<UIMessage> for FunctionPlotState<'a> {
         let builder = ChartBuilder::on(&root);
         self.build_chart(state, builder);
     }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (Status, Option<UIMessage>) {
+        let Event::Mouse(event) = event else {
+            return (Status::Ignored, None);
+        };
+        // dragging goes on even if cursor leaves the chart
+        let Some(pixel) = cursor.position().map(|position| position.x - bounds.x) else {
+            return (Status::Ignored, None);
+        };
+        let Some(x) = self
+            .state
+            .x_axis
+            .borrow()
+            .as_ref()
+            .map(|x_axis| x_axis.value(pixel))
+        else {
+            return (Status::Ignored, None);
+        };
+
+        match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) if cursor.is_over(bounds) => {
+                match self.line_at(pixel) {
+                    Some(line) => {
+                        state.dragged = Some((line, self.drag_target(line, x)));
+                        (Status::Captured, None)
+                    }
+                    None => (Status::Ignored, None),
+                }
+            }
+            mouse::Event::CursorMoved { .. } => match &mut state.dragged {
+                Some((line, dragged_x)) => {
+                    *dragged_x = self.drag_target(*line, x);
+                    (Status::Captured, None)
+                }
+                None => (Status::Ignored, None),
+            },
+            mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                let (Some((line, x)), Some(mut approximations)) =
+                    (state.dragged.take(), self.state.initial_approximations)
+                else {
+                    return (Status::Ignored, None);
+                };
+                match line {
+                    ApproximationLine::Left => approximations.left = x,
+                    ApproximationLine::Right => approximations.right = x,
+                }
+
+                (
+                    Status::Captured,
+                    Some(UIMessage::InitialApproximationsSet(approximations)),
+                )
+            }
+            _ => (Status::Ignored, None),
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> Interaction {
+        if state.dragged.is_some() {
+            return Interaction::Grabbing;
+        }
+
+        match cursor.position_in(bounds) {
+            Some(position) if self.line_at(position.x).is_some() => Interaction::Grab,
+            _ => Interaction::default(),
+        }
+    }
 }
 
 fn with_coord_margin(range: Range<TNumber>, margin_percents: TNumber) -> Range<TNumber> {
@@ -326,6 +604,24 @@ fn draw_vertical_line<DB: DrawingBackend>(
         .unwrap();
 }
 
+/// Shaded area between `left` and `right`
+fn draw_band<DB: DrawingBackend>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
+    left: TNumber,
+    right: TNumber,
+) {
+    use plotters::style::full_palette::BLUE;
+
+    const BAND_OPACITY: f64 = 0.3;
+
+    chart
+        .draw_series([plotters::element::Rectangle::new(
+            [(left, chart.y_range().start), (right, chart.y_range().end)],
+            BLUE.mix(BAND_OPACITY).filled(),
+        )])
+        .unwrap();
+}
+
 fn draw_series<DB: DrawingBackend>(
     chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf32, RangedCoordf32>>,
     function_points: &[Point],
@@ -1,10 +1,13 @@
 use std::fmt::Debug;
 
+use client::discovery::PortCandidate;
+use client::Catalogue;
+use expression::{Expression, ExpressionError};
 use function_plot::FunctionPlot;
 use iced::futures::channel::mpsc::{self, Sender};
 use iced::theme::{self};
 use iced::widget::text_input;
-use iced::widget::{button, column};
+use iced::widget::{button, checkbox, column};
 use iced::widget::{pick_list, Row};
 use iced::widget::{row, Text};
 use iced::{command, Alignment, Length, Padding};
@@ -12,28 +15,70 @@ use iced::{widget::Column, Element, Settings};
 use iced::{Application, Command};
 
 use iced_aw::{tabs::Tabs, TabLabel};
+use protocol::device_info::DeviceInfo;
+use protocol::expression::{Program, ProgramTarget};
 use protocol::point::Point;
-use protocol::request::payloads::ComputeRootPayload;
+use protocol::request::compute_method::SystemMethod;
+use protocol::request::payloads::{ComputeRootPayload, StoppingCriterion};
 use protocol::request::{self, compute_method::Method, EquationModeRaw, RequestPackage};
 use protocol::request::{Selection, SingleEquation};
-use protocol::response::{ComputeRootResponse, ResponsePackage};
-use protocol::TNumber;
-use serial_port_thread::start_loop;
-
+use protocol::response::{
+    ComputeRootResponse, InitialApproximationsResponse, MethodError, ResponsePackage,
+};
+use protocol::{TNumber, POINT_AMOUNT, PROTOCOL_VERSION};
+use serial_port_thread::{start_loop, ConnectionState, Job, Link};
+
+mod cli;
+mod config;
+mod expression;
 mod function_plot;
 mod serial_port_thread;
+mod simulator;
+mod trace_table;
 
 // don't know which sice is appropriate
 const CHANNEL_SIZE: usize = 100;
 
-#[derive(Debug, Clone, Copy)]
+const ROW_SPACING: f32 = 7.;
+const COLUMN_SPACING: f32 = 10.;
+const NUMBER_INPUT_WIDTH: f32 = 80.;
+const ERROR_COLOR: [f32; 3] = [0.8, 0.141, 0.004];
+const CONNECTED_COLOR: [f32; 3] = [0.596, 0.592, 0.102];
+
+#[derive(Debug, Clone)]
 enum UIMessage {
     // not interested in payload
     TabSelect(EquationModeRaw),
     MethodSelect(request::compute_method::Method),
+    SystemMethodSelect(SystemMethod),
     Epsilon(TNumber),
+    MaxIterations(u16),
+    CriterionSelect(StoppingCriterion),
+    TraceToggle(bool),
+    FindAllRootsToggle(bool),
     SingleEquationSelect(u8),
     SystemOfEquationsSelect(u8),
+    UserEquationInput(usize, String),
+    /// Uploads equation into device's slot and selects it
+    UserEquationSelect(usize),
+    UserEquationAdd,
+    UserEquationRemove(usize),
+    /// Link to device went up or down
+    ConnectionState(ConnectionState),
+    /// Device could not be told apart among these ports. User picks one
+    PortsDiscovered(Vec<PortCandidate>),
+    PortSelect(PortCandidate),
+    /// Handshake is complete. Device may turn out to be incompatible
+    DeviceConnected(DeviceInfo),
+    EquationsListed(Catalogue),
+    /// Result per interval with isolated root
+    AllRootsFound(Selection, Vec<Result<ComputeRootResponse, MethodError>>),
+    LeftApproximationInput(String),
+    RightApproximationInput(String),
+    /// Entered approximations are submitted
+    ApproximationsSubmit,
+    /// Approximation line is dragged on the chart
+    InitialApproximationsSet(InitialApproximationsResponse),
     /// Request contains context, such as for which equation
     /// points were requested. This eliminates class of bugs
     /// related to incoherent app state between request and response.
@@ -50,7 +95,70 @@ fn main() -> iced::Result {
 
     // thread is initialized from within `subscribe`
 
-    ComputeRootUI::run(Settings::default())
+    let invocation = match config::invocation() {
+        Ok(invocation) => invocation,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(command) = invocation.command {
+        std::process::exit(cli::run(invocation.link, command, invocation.json));
+    }
+
+    ComputeRootUI::run(Settings::with_flags(invocation.link))
+}
+
+/// Equation typed in by user. Uploaded into device's slot once selected
+#[derive(Debug, Clone)]
+struct UserEquation {
+    text: String,
+    /// Compiled on every edit, so errors show up while typing
+    compiled: Result<CompiledEquation, ExpressionError>,
+}
+
+/// Function along with its derivatives
+#[derive(Debug, Clone)]
+struct CompiledEquation {
+    /// Shown to user to check against their own
+    derivative: Expression,
+    /// In order of upload. Device approximates derivatives which did not fit
+    programs: Vec<(ProgramTarget, Program)>,
+}
+
+impl UserEquation {
+    fn new(text: String) -> Self {
+        let compiled = Expression::parse(&text).and_then(|function| {
+            let derivative = function.derivative();
+            let second_derivative = derivative.derivative();
+
+            let mut programs = vec![(ProgramTarget::Function, function.compile()?)];
+            programs.extend(
+                [
+                    (ProgramTarget::FirstDerivative, &derivative),
+                    (ProgramTarget::SecondDerivative, &second_derivative),
+                ]
+                .into_iter()
+                .filter_map(|(target, expression)| Some((target, expression.compile().ok()?))),
+            );
+
+            Ok(CompiledEquation {
+                derivative,
+                programs,
+            })
+        });
+
+        Self { text, compiled }
+    }
+}
+
+impl CompiledEquation {
+    fn is_derivative_uploaded(&self) -> bool {
+        self.programs
+            .iter()
+            .any(|(target, _)| *target == ProgramTarget::FirstDerivative)
+    }
 }
 
 #[derive(Debug)]
@@ -58,10 +166,33 @@ struct ComputeRootUI {
     // this structure contains everything required to represent ui
     // and to send request
     epsilon: TNumber,
+    max_iterations: u16,
+    criterion: StoppingCriterion,
     mode: EquationModeRaw,
     single_equation: SingleEquation,
     system_of_equations_number: u8,
-    serial_port: Sender<RequestPackage>,
+    system_method: SystemMethod,
+    /// Request state of each iteration along with the root
+    show_trace: bool,
+    /// Look for every root within plotted range instead of initial approximations
+    find_all_roots: bool,
+    connection: ConnectionState,
+    /// `None` until handshake is complete. Kept once connection is lost
+    device_info: Option<DeviceInfo>,
+    /// Empty until device lists its equations
+    catalogue: Catalogue,
+    user_equations: Vec<UserEquation>,
+    /// Index of the user equation which is in device's slot
+    uploaded_equation: Option<usize>,
+    /// Approximations as they are typed in.
+    /// Replaced, whenever device reports approximations
+    left_input: String,
+    right_input: String,
+    serial_port: Sender<Job>,
+    /// Listed when port could not be discovered
+    ports: Vec<PortCandidate>,
+    selected_port: Option<PortCandidate>,
+    port_choices: Sender<String>,
     plot: FunctionPlot,
 }
 
@@ -80,23 +211,82 @@ impl ComputeRootUI {
     fn build_compute_root_payload(&self) -> ComputeRootPayload {
         ComputeRootPayload {
             epsilon: self.epsilon,
+            max_iterations: self.max_iterations,
+            criterion: self.criterion,
             mode: match self.mode {
                 EquationModeRaw::SingleEquation => {
                     request::EquationMode::Single(self.single_equation)
                 }
                 EquationModeRaw::SystemOfEquations => request::EquationMode::SystemOfEquations {
+                    method: self.system_method,
                     system_number: self.system_of_equations_number,
                 },
             },
         }
     }
+
+    fn build_compute_root_request(&self) -> RequestPackage {
+        let payload = self.build_compute_root_payload();
+        if self.is_finding_all_roots() {
+            return RequestPackage::FindAllRoots { payload };
+        }
+
+        match self.show_trace && self.supports(DeviceInfo::supports_trace) {
+            true => RequestPackage::ComputeRootTrace { payload },
+            false => RequestPackage::ComputeRoot { payload },
+        }
+    }
+
+    /// Roots of systems can not be found all at once
+    fn is_finding_all_roots(&self) -> bool {
+        self.find_all_roots
+            && self.mode == EquationModeRaw::SingleEquation
+            && self.supports(DeviceInfo::supports_find_all_roots)
+    }
+
+    /// Everything is considered supported until device tells otherwise
+    fn supports(&self, check: impl Fn(&DeviceInfo) -> bool) -> bool {
+        self.device_info.as_ref().is_none_or(check)
+    }
+
+    /// Discovery found no device or several of them. Connection waits for user to pick a port
+    fn is_picking_port(&self) -> bool {
+        self.connection == ConnectionState::Connecting && !self.ports.is_empty()
+    }
+
+    fn is_device_incompatible(&self) -> bool {
+        !self.supports(DeviceInfo::is_compatible)
+    }
+
+    fn upload_equation(&mut self, index: usize) {
+        let compiled = self.user_equations[index]
+            .compiled
+            .as_ref()
+            .expect("only valid equations are uploaded");
+        // single job rather than a request per chunk, so channel is not flooded
+        self.serial_port
+            .try_send(Job::UploadEquation(compiled.programs.clone()))
+            .expect("could upload equation");
+    }
+
+    /// Index of the single equation which is uploaded by host.
+    /// `None` until device tells it has one
+    fn user_slot(&self) -> Option<u8> {
+        let supported = self
+            .device_info
+            .is_some_and(|info| info.supports_upload_equation());
+        match (supported, self.catalogue.single.len()) {
+            (true, length) if length > 0 => Some(length as u8 - 1),
+            _ => None,
+        }
+    }
 }
 
 impl Application for ComputeRootUI {
     type Message = UIMessage;
     type Executor = iced::executor::Default;
     type Theme = iced::Theme;
-    type Flags = ();
+    type Flags = Link;
 
     fn title(&self) -> String {
         String::from("A cool application")
@@ -107,34 +297,150 @@ impl Application for ComputeRootUI {
         match message {
             UIMessage::TabSelect(mode) => self.mode = mode,
             UIMessage::MethodSelect(method) => self.single_equation.method = method,
+            UIMessage::SystemMethodSelect(method) => self.system_method = method,
             UIMessage::Epsilon(epsilon) => self.epsilon = epsilon,
+            UIMessage::MaxIterations(max_iterations) => self.max_iterations = max_iterations,
+            UIMessage::CriterionSelect(criterion) => self.criterion = criterion,
+            UIMessage::TraceToggle(show_trace) => self.show_trace = show_trace,
+            UIMessage::FindAllRootsToggle(find_all_roots) => self.find_all_roots = find_all_roots,
+            UIMessage::AllRootsFound(selection, ref roots) => {
+                self.plot.set_all_roots(selection, roots.clone())
+            }
             UIMessage::SingleEquationSelect(equation_number) => {
                 self.single_equation.equation_number = equation_number
             }
             UIMessage::ResponseReceived(ref request, response) => {
                 approx_changed = self.plot.has_intial_approximations_changed(&response);
+                if let ResponsePackage::InitialApproximations(approximations) = response {
+                    self.left_input = format!("{:.4}", approximations.left);
+                    self.right_input = format!("{:.4}", approximations.right);
+                }
                 self.plot.update(request, response);
             }
             UIMessage::SystemOfEquationsSelect(system_number) => {
                 self.system_of_equations_number = system_number
             }
+            UIMessage::UserEquationInput(index, ref text) => {
+                self.user_equations[index] = UserEquation::new(text.clone())
+            }
+            UIMessage::UserEquationSelect(index) => {
+                let (Some(slot), Ok(_)) = (self.user_slot(), &self.user_equations[index].compiled)
+                else {
+                    return Command::none();
+                };
+
+                self.single_equation.equation_number = slot;
+                self.uploaded_equation = Some(index);
+                self.plot.reset_single(slot);
+            }
+            UIMessage::UserEquationAdd => {
+                self.user_equations.push(UserEquation::new(String::new()))
+            }
+            UIMessage::UserEquationRemove(index) => {
+                self.user_equations.remove(index);
+                self.uploaded_equation = match self.uploaded_equation {
+                    // device keeps the program, but there is nothing to re-upload
+                    Some(uploaded) if uploaded == index => None,
+                    Some(uploaded) if uploaded > index => Some(uploaded - 1),
+                    uploaded => uploaded,
+                };
+            }
+            UIMessage::ConnectionState(state) => {
+                self.connection = state;
+                if matches!(state, ConnectionState::Connected | ConnectionState::Lost) {
+                    self.ports.clear();
+                    self.selected_port = None;
+                }
+            }
+            UIMessage::PortsDiscovered(ref ports) => self.ports = ports.clone(),
+            UIMessage::PortSelect(ref port) => {
+                self.selected_port = Some(port.clone());
+                self.port_choices
+                    .try_send(port.path.clone())
+                    .expect("could choose port");
+            }
+            UIMessage::DeviceConnected(device_info) => self.device_info = Some(device_info),
+            UIMessage::EquationsListed(ref catalogue) => {
+                self.plot.set_catalogue(catalogue);
+                self.catalogue = catalogue.clone();
+            }
+            UIMessage::LeftApproximationInput(ref input) => self.left_input = input.clone(),
+            UIMessage::RightApproximationInput(ref input) => self.right_input = input.clone(),
+            UIMessage::ApproximationsSubmit | UIMessage::InitialApproximationsSet(_) => (),
         };
 
+        // serial port thread is gone. Nobody would read requests.
+        // Everything is requested again once device is connected
+        if self.is_device_incompatible() || self.connection != ConnectionState::Connected {
+            return Command::none();
+        }
+
         // should not take too long to send single structure to the serial
         // port thread synchronously
         match message {
             UIMessage::TabSelect(_)
             | UIMessage::SingleEquationSelect(_)
-            | UIMessage::SystemOfEquationsSelect(_) => {
+            | UIMessage::SystemOfEquationsSelect(_)
+            | UIMessage::EquationsListed(_) => {
                 let request = RequestPackage::FunctionPoints {
                     payload: self.build_selection(),
                 };
 
                 self.serial_port
-                    .try_send(request)
+                    .try_send(Job::Request(request))
+                    .expect("could request function points");
+            }
+
+            UIMessage::DeviceConnected(_) => {
+                // device might have been reset, so its slot is empty.
+                // Equation could have been edited into invalid one since
+                if let Some(index) = self.uploaded_equation {
+                    match self.user_equations[index].compiled.is_ok() {
+                        true => self.upload_equation(index),
+                        false => self.uploaded_equation = None,
+                    }
+                }
+
+                // points and root are requested once equations are known
+                self.serial_port
+                    .try_send(Job::Request(RequestPackage::ListEquations))
+                    .expect("Could request equations");
+                // later changes are reported by device on its own
+                self.serial_port
+                    .try_send(Job::Request(RequestPackage::InitialApproximations))
+                    .expect("Could request initial approximations");
+            }
+
+            UIMessage::UserEquationSelect(index) => {
+                // device handles requests in order, so points are computed for the new equation
+                self.upload_equation(index);
+                self.serial_port
+                    .try_send(Job::Request(RequestPackage::FunctionPoints {
+                        payload: self.build_selection(),
+                    }))
                     .expect("could request function points");
             }
 
+            UIMessage::ApproximationsSubmit => {
+                // device validates range on its own
+                if let (Ok(left), Ok(right)) = (self.left_input.parse(), self.right_input.parse()) {
+                    self.serial_port
+                        .try_send(Job::Request(RequestPackage::SetInitialApproximations {
+                            left,
+                            right,
+                        }))
+                        .expect("could set initial approximations");
+                }
+            }
+            UIMessage::InitialApproximationsSet(InitialApproximationsResponse { left, right }) => {
+                self.serial_port
+                    .try_send(Job::Request(RequestPackage::SetInitialApproximations {
+                        left,
+                        right,
+                    }))
+                    .expect("could set initial approximations");
+            }
+
             _ => (),
         };
 
@@ -142,46 +448,77 @@ impl Application for ComputeRootUI {
             || match message {
                 UIMessage::TabSelect(_)
                 | UIMessage::MethodSelect(_)
+                | UIMessage::SystemMethodSelect(_)
                 | UIMessage::Epsilon(_)
+                | UIMessage::MaxIterations(_)
+                | UIMessage::CriterionSelect(_)
+                | UIMessage::TraceToggle(_)
+                | UIMessage::FindAllRootsToggle(_)
                 | UIMessage::SystemOfEquationsSelect(_)
-                | UIMessage::SingleEquationSelect(_) => true,
+                | UIMessage::SingleEquationSelect(_)
+                | UIMessage::UserEquationSelect(_)
+                | UIMessage::EquationsListed(_) => true,
                 _ => false,
             };
 
         if should_update_root {
-            let payload = self.build_compute_root_payload();
-            let request = RequestPackage::ComputeRoot { payload };
+            let request = self.build_compute_root_request();
 
             self.serial_port
-                .try_send(request)
+                .try_send(Job::Request(request))
                 .expect("could request root");
         }
 
         Command::none()
-        // todo!();
     }
 
     fn view(&self) -> Element<Self::Message> {
-        // dbg!(self);
-
-        let single_equations = ["x^2 + x + sin(x)", "ln(x) + 15"]
-            .into_iter()
+        let user_slot = self.user_slot();
+        // slot is listed as user equations instead
+        let flashed_amount = match user_slot {
+            Some(slot) => slot as usize,
+            None => self.catalogue.single.len(),
+        };
+        let single_equations = self.catalogue.single[..flashed_amount]
+            .iter()
             .enumerate()
             .map(|(index, equation)| {
-                let text_container = Column::new()
-                    .width(Length::Fill)
-                    .align_items(iced::Alignment::Center)
-                    .push(equation);
-                let mut item = button(text_container)
-                    .padding(10)
-                    .width(Length::Fill)
-                    .on_press(UIMessage::SingleEquationSelect(index as u8));
-
-                if index != self.single_equation.equation_number as usize {
-                    item = item.style(theme::Button::Secondary);
-                }
-
-                item.into()
+                equation_button(
+                    Text::new(equation).into(),
+                    index == self.single_equation.equation_number as usize,
+                    UIMessage::SingleEquationSelect(index as u8),
+                )
+            });
+        let user_equations = user_slot.map(|slot| {
+            let is_slot_selected = slot == self.single_equation.equation_number;
+            let entries = self
+                .user_equations
+                .iter()
+                .enumerate()
+                .map(|(index, equation)| {
+                    let is_uploaded = self.uploaded_equation == Some(index);
+                    user_equation_entry(equation, index, is_slot_selected && is_uploaded)
+                });
+
+            Column::with_children(entries)
+                .push(button("Add equation").on_press(UIMessage::UserEquationAdd))
+                .spacing(COLUMN_SPACING)
+                .align_items(iced::Alignment::Center)
+        });
+
+        let systems = self
+            .catalogue
+            .systems
+            .iter()
+            .enumerate()
+            .map(|(index, (first, second))| {
+                equation_button(
+                    column!(Text::new(first), Text::new(second))
+                        .align_items(iced::Alignment::Center)
+                        .into(),
+                    index == self.system_of_equations_number as usize,
+                    UIMessage::SystemOfEquationsSelect(index as u8),
+                )
             });
 
         // Padding between tabs labels and tabs themselves
@@ -190,13 +527,12 @@ impl Application for ComputeRootUI {
             ..Padding::ZERO
         };
 
-        const ROW_SPACING: f32 = 7.;
-        const COLUMN_SPACING: f32 = 10.;
         let single_equation_tab = Column::new()
             .push(
                 Column::with_children(single_equations)
+                    .push_maybe(user_equations)
                     // width is considered as min_width by iced
-                    .width(Length::Fixed(300.))
+                    .width(Length::Fixed(400.))
                     .spacing(COLUMN_SPACING)
                     .align_items(iced::Alignment::Center),
             )
@@ -204,10 +540,15 @@ impl Application for ComputeRootUI {
             .padding(tabs_padding)
             .align_items(iced::Alignment::Center);
 
-        let system_of_equations_tab = column!("1 - sin(x) / 2 - x", "0.7 - cos(y - 1) - y")
-            .spacing(COLUMN_SPACING)
-            .padding(tabs_padding)
+        let system_of_equations_tab = Column::new()
+            .push(
+                Column::with_children(systems)
+                    .width(Length::Fixed(300.))
+                    .spacing(COLUMN_SPACING)
+                    .align_items(iced::Alignment::Center),
+            )
             .width(Length::Fill)
+            .padding(tabs_padding)
             .align_items(iced::Alignment::Center);
 
         let tabs_descriptor = Tabs::new(UIMessage::TabSelect)
@@ -230,6 +571,44 @@ impl Application for ComputeRootUI {
         )
         .spacing(ROW_SPACING)
         .align_items(iced::Alignment::Center))
+        .push(
+            row!(
+                "Max iterations:",
+                text_input("", &self.max_iterations.to_string())
+                    .on_input(|input| {
+                        UIMessage::MaxIterations(input.parse().unwrap_or(self.max_iterations))
+                    })
+                    .width(Length::Fixed(NUMBER_INPUT_WIDTH)),
+                "Stop on:",
+                pick_list(
+                    [
+                        StoppingCriterion::Step,
+                        StoppingCriterion::Residual,
+                        StoppingCriterion::Both,
+                    ],
+                    Some(self.criterion),
+                    UIMessage::CriterionSelect
+                )
+            )
+            .spacing(ROW_SPACING)
+            .align_items(iced::Alignment::Center),
+        )
+        .push(
+            row!(
+                "Left:",
+                text_input("", &self.left_input)
+                    .on_input(UIMessage::LeftApproximationInput)
+                    .on_submit(UIMessage::ApproximationsSubmit)
+                    .width(Length::Fixed(NUMBER_INPUT_WIDTH)),
+                "Right:",
+                text_input("", &self.right_input)
+                    .on_input(UIMessage::RightApproximationInput)
+                    .on_submit(UIMessage::ApproximationsSubmit)
+                    .width(Length::Fixed(NUMBER_INPUT_WIDTH))
+            )
+            .spacing(ROW_SPACING)
+            .align_items(iced::Alignment::Center),
+        )
         .spacing(ROW_SPACING)
         .align_items(iced::Alignment::Center);
 
@@ -238,33 +617,107 @@ impl Application for ComputeRootUI {
                 row!(
                     "method:",
                     pick_list(
-                        [Method::Chord, Method::Secant, Method::SimpleIterationSingle],
+                        [
+                            Method::Chord,
+                            Method::Secant,
+                            Method::SimpleIterationSingle,
+                            Method::Newton,
+                            Method::Bisection,
+                        ]
+                        .into_iter()
+                        .filter(|&method| self.supports(|info| info.supports_method(method)))
+                        .collect::<Vec<_>>(),
                         Some(self.single_equation.method),
-                        |method| UIMessage::MethodSelect(method)
+                        UIMessage::MethodSelect
                     )
                 )
                 .spacing(ROW_SPACING)
                 .align_items(iced::Alignment::Center),
             )
         } else {
-            parameters_row
+            parameters_row.push(
+                row!(
+                    "method:",
+                    pick_list(
+                        [SystemMethod::SimpleIteration, SystemMethod::Newton]
+                            .into_iter()
+                            .filter(
+                                |&method| self.supports(|info| info.supports_system_method(method))
+                            )
+                            .collect::<Vec<_>>(),
+                        Some(self.system_method),
+                        UIMessage::SystemMethodSelect
+                    )
+                )
+                .spacing(ROW_SPACING)
+                .align_items(iced::Alignment::Center),
+            )
+        };
+
+        let parameters_row = match self.supports(DeviceInfo::supports_trace) {
+            true => parameters_row.push(
+                checkbox("Show iterations", self.show_trace).on_toggle(UIMessage::TraceToggle),
+            ),
+            false => parameters_row,
+        };
+        let parameters_row = match self.mode == EquationModeRaw::SingleEquation
+            && self.supports(DeviceInfo::supports_find_all_roots)
+        {
+            true => parameters_row.push(
+                checkbox("All roots in range", self.find_all_roots)
+                    .on_toggle(UIMessage::FindAllRootsToggle),
+            ),
+            false => parameters_row,
         };
 
         let selection = self.build_selection();
         let maybe_compute_root = self.plot.get_compute_root(selection);
+        let maybe_all_roots = self.plot.get_all_roots(selection);
         let (output, is_error) = {
-            match maybe_compute_root {
-                Some(Ok(ComputeRootResponse {
-                    root: Point { x, y },
-                })) => (format!("x: {x:.4}; y: {y:.4}"), false),
-                Some(Err(err)) => (err.to_string(), true),
-                None => ("Loading...".to_owned(), false),
+            match (maybe_compute_root, maybe_all_roots) {
+                _ if self.is_device_incompatible() => {
+                    let device_info = self
+                        .device_info
+                        .expect("device is known to be incompatible");
+                    (
+                        format!(
+                            "Incompatible firmware: protocol version {}, {} points. \
+                             Expected protocol version {}, {} points. Reflash the device",
+                            device_info.version,
+                            device_info.point_amount,
+                            PROTOCOL_VERSION,
+                            POINT_AMOUNT
+                        ),
+                        true,
+                    )
+                }
+                (_, Some(roots)) => (describe_roots(roots), false),
+                (
+                    Some(Ok(ComputeRootResponse {
+                        root: Point { x, y },
+                        iterations,
+                        step,
+                        residual,
+                    })),
+                    None,
+                ) => (
+                    format!(
+                        "x: {x:.4}; y: {y:.4}; iterations: {iterations}; \
+                         step: {step:.2e}; residual: {residual:.2e}"
+                    ),
+                    false,
+                ),
+                (Some(Err(err)), None) => (err.to_string(), true),
+                (None, None) if self.is_picking_port() => {
+                    ("Pick the port arduino is plugged in".to_owned(), false)
+                }
+                (None, None) => ("Loading...".to_owned(), false),
             }
         };
 
         let output_element = match is_error {
             false => Text::new(output),
-            true => Text::new(output).style(theme::Text::Color([0.8, 0.141, 0.004].into())),
+            true => Text::new(output).style(theme::Text::Color(ERROR_COLOR.into())),
         };
 
         let output_row = Row::new()
@@ -272,11 +725,54 @@ impl Application for ComputeRootUI {
             .push(output_element)
             .spacing(ROW_SPACING)
             .align_items(Alignment::Center);
+        let suggestion = maybe_compute_root.and_then(|root| root.err()?.suggestion());
+        let output_row = match suggestion {
+            Some(suggestion) if !self.is_device_incompatible() => output_row.push(
+                button("Use suggested bracket")
+                    .on_press(UIMessage::InitialApproximationsSet(suggestion)),
+            ),
+            _ => output_row,
+        };
+
+        let trace_table = match self.show_trace
+            && self.supports(DeviceInfo::supports_trace)
+            && !self.is_finding_all_roots()
+        {
+            true => Some(trace_table::view(
+                self.plot.get_trace(selection),
+                selection.mode,
+            )),
+            false => None,
+        };
+
+        let connection_color = match self.connection {
+            ConnectionState::Connected => Some(CONNECTED_COLOR),
+            ConnectionState::Lost => Some(ERROR_COLOR),
+            ConnectionState::Connecting | ConnectionState::Handshaking => None,
+        };
+        let connection_badge =
+            Text::new(self.connection.to_string()).style(match connection_color {
+                Some(color) => theme::Text::Color(color.into()),
+                None => theme::Text::Default,
+            });
+        let port_picker = self.is_picking_port().then(|| {
+            pick_list(
+                self.ports.clone(),
+                self.selected_port.clone(),
+                UIMessage::PortSelect,
+            )
+        });
+        let connection_row = row!("Device:", connection_badge)
+            .push_maybe(port_picker)
+            .spacing(ROW_SPACING)
+            .align_items(Alignment::Center);
 
         Column::new()
+            .push(connection_row)
             .push(tabs_descriptor.set_active_tab(&self.mode))
             .push(parameters_row)
             .push(output_row)
+            .push_maybe(trace_table)
             .push(self.plot.view(selection))
             .spacing(COLUMN_SPACING)
             .width(Length::Fill)
@@ -291,7 +787,7 @@ impl Application for ComputeRootUI {
         iced::theme::Application::default()
     }
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
+    fn new(link: Self::Flags) -> (Self, Command<Self::Message>) {
         // consider subscription as deprecated concept
         // new allows to return command which runs upon app initialization
         // here is the right place to init serial port thread
@@ -304,42 +800,129 @@ impl Application for ComputeRootUI {
         // distinguish between approximation change and response
         // messages -- they all can be handled uniformly
         let (command_sender, command_receiver) = mpsc::channel(CHANNEL_SIZE);
+        let (port_sender, port_receiver) = mpsc::channel(CHANNEL_SIZE);
         let default_choice = Selection {
             mode: EquationModeRaw::SingleEquation,
             index: 0,
         };
 
-        let mut compute_root_ui = ComputeRootUI {
+        let compute_root_ui = ComputeRootUI {
             epsilon: 0.0625,
+            max_iterations: 1000,
+            criterion: StoppingCriterion::Step,
             mode: default_choice.mode,
             single_equation: SingleEquation {
                 method: Method::Chord,
                 equation_number: default_choice.index as u8,
             },
             system_of_equations_number: default_choice.index as u8,
+            system_method: SystemMethod::SimpleIteration,
+            show_trace: false,
+            find_all_roots: false,
+            connection: ConnectionState::Connecting,
+            device_info: None,
+            catalogue: Catalogue::default(),
+            user_equations: vec![UserEquation::new("x^3 - 2*cos(x) + 1".to_owned())],
+            uploaded_equation: None,
+            left_input: String::new(),
+            right_input: String::new(),
             serial_port: command_sender,
+            ports: Vec::new(),
+            selected_port: None,
+            port_choices: port_sender,
             plot: FunctionPlot::new(),
         };
 
-        compute_root_ui
-            .serial_port
-            .try_send(RequestPackage::FunctionPoints {
-                payload: compute_root_ui.build_selection(),
-            })
-            .expect("Could request function's points");
-
-        compute_root_ui
-            .serial_port
-            .try_send(RequestPackage::ComputeRoot {
-                payload: compute_root_ui.build_compute_root_payload(),
-            })
-            .expect("could request root for selected equation upon start");
-
         (
             compute_root_ui,
             command::channel(CHANNEL_SIZE, move |sender| {
-                start_loop(command_receiver, sender)
+                start_loop(link, command_receiver, port_receiver, sender)
             }),
         )
     }
 }
+
+/// Roots which were found, followed by amount of intervals method failed on
+fn describe_roots(roots: &[Result<ComputeRootResponse, MethodError>]) -> String {
+    let found = roots
+        .iter()
+        .flatten()
+        .map(|response| format!("{:.4}", response.root.x))
+        .collect::<Vec<_>>();
+    let failed = roots.len() - found.len();
+
+    let mut description = match found.is_empty() {
+        true => "No roots found".to_owned(),
+        false => format!("roots: {}", found.join(", ")),
+    };
+    if failed > 0 {
+        description += &format!("; method failed on {failed} intervals");
+    }
+
+    description
+}
+
+/// Editable equation. Its derivative or errors are shown right below it
+fn user_equation_entry(
+    equation: &UserEquation,
+    index: usize,
+    is_selected: bool,
+) -> Element<'_, UIMessage> {
+    let select = button("Use").on_press(UIMessage::UserEquationSelect(index));
+    let select = match is_selected {
+        true => select,
+        false => select.style(theme::Button::Secondary),
+    };
+    // equation in device's slot stays listed
+    let remove = button("Remove")
+        .style(theme::Button::Secondary)
+        .on_press_maybe((!is_selected).then_some(UIMessage::UserEquationRemove(index)));
+
+    let input = row!(
+        text_input("f(x)", &equation.text)
+            .on_input(move |text| UIMessage::UserEquationInput(index, text))
+            .on_submit(UIMessage::UserEquationSelect(index)),
+        select,
+        remove
+    )
+    .spacing(ROW_SPACING)
+    .align_items(Alignment::Center);
+
+    let details = match &equation.compiled {
+        Ok(compiled) => {
+            let mut derivative = format!("f'(x) = {}", compiled.derivative);
+            if !compiled.is_derivative_uploaded() {
+                derivative += " (too long, device approximates it)";
+            }
+            Some(Text::new(derivative))
+        }
+        Err(error) if !equation.text.is_empty() => {
+            Some(Text::new(error.to_string()).style(theme::Text::Color(ERROR_COLOR.into())))
+        }
+        Err(_) => None,
+    };
+
+    Column::new().push(input).push_maybe(details).into()
+}
+
+/// Selectable equation within a tab
+fn equation_button(
+    content: Element<UIMessage>,
+    is_selected: bool,
+    message: UIMessage,
+) -> Element<UIMessage> {
+    let text_container = Column::new()
+        .width(Length::Fill)
+        .align_items(iced::Alignment::Center)
+        .push(content);
+    let mut item = button(text_container)
+        .padding(10)
+        .width(Length::Fill)
+        .on_press(message);
+
+    if !is_selected {
+        item = item.style(theme::Button::Secondary);
+    }
+
+    item.into()
+}
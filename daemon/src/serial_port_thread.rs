@@ -1,114 +1,304 @@
 use std::error::Error;
-use std::io::{self};
+use std::fmt::Display;
+use std::ops::ControlFlow;
 use std::time::Duration;
 
+use client::discovery::{self, PortCandidate};
+use client::{ClientError, Device};
 use iced::futures::channel::mpsc::{Receiver, Sender};
+use iced::futures::{SinkExt, StreamExt};
+use protocol::expression::{Program, ProgramTarget};
+use protocol::notification::Notification;
+use protocol::request::payloads::UploadChunk;
+use protocol::request::RequestPackage;
+use protocol::response::ResponsePackage;
 
-use iced::futures::SinkExt;
-use protocol::response::{ComputeRootResponse, MethodError, ResponsePackage};
-use protocol::{byte_serializable::ByteSerializable, request::RequestPackage};
-use protocol::{is_signature_valid, PROTOCOL_SIGNATURE, PROTOCOL_SIGNATURE_SIZE};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio_serial::{ClearBuffer, SerialPort, SerialPortBuilderExt, SerialStream};
-
-use crate::UIMessage;
+use crate::config::PortSettings;
+use crate::{simulator, UIMessage};
 
 // connect
 // verify signature
 // enter main loop
 
-pub async fn start_loop(packages: Receiver<RequestPackage>, messages: Sender<UIMessage>) {
-    let serial_port = tokio_serial::new("/dev/ttyACM0", 250_000)
-        .open_native_async()
-        .expect("succesfully open");
+/// How often approximations are requested from devices which do not send notifications
+const POLL_PERIOD: Duration = Duration::from_millis(500);
+/// Delay before reconnection. Doubled after each failed attempt
+const MIN_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(8);
+/// How often ports are probed again while none of them responds
+const REDISCOVERY_PERIOD: Duration = Duration::from_secs(10);
+/// Device which misses that many responses in a row is hung, even though its port is still open
+const MAX_SUCCESSIVE_TIMEOUTS: usize = 3;
+
+/// What daemon talks to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    /// Arduino plugged in
+    SerialPort(PortSettings),
+    /// Device running within daemon, see [`simulator`]
+    Simulator,
+}
+
+/// What UI asks connection thread to do
+#[derive(Debug, Clone)]
+pub enum Job {
+    Request(RequestPackage),
+    /// Programs in order of upload. Split into chunks here, so a long program
+    /// takes a single slot of the channel along with queued requests
+    UploadEquation(Vec<(ProgramTarget, Program)>),
+}
+
+/// Reported to user as the link goes up and down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Looking for device or opening its port
+    Connecting,
+    /// Port is open, signature and device info are exchanged
+    Handshaking,
+    Connected,
+    /// Retried after a delay
+    Lost,
+}
+
+impl Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Handshaking => "handshaking",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Lost => "connection lost",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+/// Connects to device and reconnects once link is lost.
+/// `port_choices` are paths picked by user, if port could not be discovered
+pub async fn start_loop(
+    link: Link,
+    mut packages: Receiver<Job>,
+    mut port_choices: Receiver<String>,
+    mut messages: Sender<UIMessage>,
+) {
+    let mut delay = MIN_RECONNECT_DELAY;
+    loop {
+        let session = session(
+            &link,
+            &mut packages,
+            &mut port_choices,
+            &mut messages,
+            &mut delay,
+        );
+        match session.await {
+            Ok(ControlFlow::Break(_)) => return,
+            Ok(ControlFlow::Continue(_)) => (),
+            Err(error) => eprintln!("{}", error),
+        }
+
+        let lost = UIMessage::ConnectionState(ConnectionState::Lost);
+        if messages.send(lost).await.is_err() {
+            return;
+        }
+
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Lasts while device is connected. Breaks once there is no point to reconnect:
+/// either ui is closed or device is incompatible
+async fn session(
+    link: &Link,
+    packages: &mut Receiver<Job>,
+    port_choices: &mut Receiver<String>,
+    messages: &mut Sender<UIMessage>,
+    delay: &mut Duration,
+) -> Result<ControlFlow<()>, Box<dyn Error>> {
+    messages
+        .send(UIMessage::ConnectionState(ConnectionState::Connecting))
+        .await?;
+    let device = connect(link, port_choices, messages).await?;
+
+    let device_info = device.info();
+    *delay = MIN_RECONNECT_DELAY;
 
-    let serial_port = verify_signature(serial_port)
-        .await
-        .expect("Signature valid");
-    println!("signature verified");
+    // requests made while link was down are outdated.
+    // Once connected, ui requests everything again
+    while packages.try_recv().is_ok() {}
 
-    let read_buffer_long: [u8; protocol::LONG_PACKAGE_SIZE] = [0; protocol::LONG_PACKAGE_SIZE];
-    let read_buffer_short: [u8; protocol::PACKAGE_SIZE] = [0; protocol::PACKAGE_SIZE];
+    messages
+        .send(UIMessage::ConnectionState(ConnectionState::Connected))
+        .await?;
+    // ui explains the problem to user
+    let compatible = device_info.is_compatible();
+    if !compatible {
+        eprintln!("incompatible device: {:?}", device_info);
+    }
+    messages
+        .send(UIMessage::DeviceConnected(device_info))
+        .await?;
+    if !compatible {
+        return Ok(ControlFlow::Break(()));
+    }
 
     let mut context = LoopContext {
         packages,
-        serial_port,
-        read_buffer_long,
-        read_buffer_short,
-        messages,
+        device,
+        poll_approximations: !device_info.supports_notifications(),
+        messages: messages.clone(),
     };
 
+    let mut timeouts = 0;
+    let mut dropped_frames = 0;
     loop {
-        match loop_iteration(&mut context).await {
-            Ok(_) => continue,
-            Err(err) => eprintln!("{}", err),
+        let result = loop_iteration(&mut context).await;
+        // client skips broken frames silently, they are reported here
+        let dropped = context.device.dropped_frames();
+        if dropped > dropped_frames {
+            eprintln!("dropped {} broken frames", dropped - dropped_frames);
+            dropped_frames = dropped;
+        }
+
+        let err = match result {
+            Ok(ControlFlow::Continue(_)) => {
+                timeouts = 0;
+                continue;
+            }
+            Ok(ControlFlow::Break(_)) => return Ok(ControlFlow::Break(())),
+            Err(err) => err,
+        };
+
+        match err.downcast_ref::<ClientError>() {
+            // device is unplugged or port is gone otherwise
+            Some(client_error) if client_error.is_disconnected() => return Err(err),
+            Some(ClientError::Timeout) => {
+                timeouts += 1;
+                if timeouts >= MAX_SUCCESSIVE_TIMEOUTS {
+                    return Err(err);
+                }
+            }
+            _ => timeouts = 0,
         }
+        eprintln!("{}", err);
     }
 }
 
-struct LoopContext {
+struct LoopContext<'a> {
     messages: Sender<UIMessage>,
-    packages: Receiver<RequestPackage>,
-    serial_port: SerialStream,
-    read_buffer_long: [u8; protocol::LONG_PACKAGE_SIZE],
-    read_buffer_short: [u8; protocol::PACKAGE_SIZE],
+    packages: &'a mut Receiver<Job>,
+    device: Device,
+    /// Device does not notify about approximation changes,
+    /// so they are requested periodically
+    poll_approximations: bool,
+}
+
+/// What wakes up the main loop
+enum LoopEvent {
+    Request(RequestPackage),
+    Upload(Vec<(ProgramTarget, Program)>),
+    Notification(Notification),
+    /// UI is closed, nobody would send requests anymore
+    Closed,
 }
 
 async fn loop_iteration(
     LoopContext {
         packages,
-        serial_port,
-        read_buffer_long,
-        read_buffer_short,
+        device,
         messages,
-    }: &mut LoopContext,
-) -> Result<(), Box<dyn Error>> {
-    // check for available commands
-    // if some -> convert to requests
-    // if none -> request approximations
-
-    // try_next,'cause no need to wait for new package
-    // if we have nothing to send, send InitialApproximations request
-    let command = packages.try_next();
-    let request = if let Ok(Some(value)) = command {
-        value
-    } else {
-        RequestPackage::InitialApproximations
+        poll_approximations,
+    }: &mut LoopContext<'_>,
+) -> Result<ControlFlow<()>, Box<dyn Error>> {
+    // link stays idle until either ui sends a request or
+    // device notifies about something
+    let event = tokio::select! {
+        job = packages.next() => match job {
+            Some(Job::Request(request)) => LoopEvent::Request(request),
+            Some(Job::UploadEquation(programs)) => LoopEvent::Upload(programs),
+            None => LoopEvent::Closed,
+        },
+        notification = device.notification() => {
+            LoopEvent::Notification(notification?)
+        }
+        _ = tokio::time::sleep(POLL_PERIOD), if *poll_approximations => {
+            LoopEvent::Request(RequestPackage::InitialApproximations)
+        }
     };
 
-    AsyncWriteExt::write(serial_port, &request.to_bytes()).await?;
-    // wait for port to become readable
-    serial_port.readable().await?;
-    let read_buffer: &mut [u8] = match request {
-        RequestPackage::FunctionPoints { .. } => read_buffer_long,
-        RequestPackage::InitialApproximations => read_buffer_short,
-        RequestPackage::ComputeRoot { .. } => read_buffer_short,
-    };
-    // read and parse data
-    serial_port.read_exact(read_buffer).await?;
-    let response: protocol::response::ResponsePackage = match &request {
-        RequestPackage::FunctionPoints { payload } => match payload.mode {
-            protocol::request::EquationModeRaw::SingleEquation => {
-                protocol::response::FunctionPointsResponse::from(&*read_buffer_long).into()
+    let request = match event {
+        LoopEvent::Request(request) => request,
+        LoopEvent::Notification(Notification::InitialApproximationsChanged(approximations)) => {
+            // handled by ui the same way as requested approximations
+            messages
+                .send(UIMessage::ResponseReceived(
+                    RequestPackage::InitialApproximations,
+                    approximations.into(),
+                ))
+                .await?;
+
+            return Ok(ControlFlow::Continue(()));
+        }
+        LoopEvent::Upload(programs) => {
+            // function goes first: it drops derivatives of the previous one
+            for (target, program) in &programs {
+                for chunk in UploadChunk::split(*target, program) {
+                    device.upload_equation(chunk).await?;
+                }
             }
-            protocol::request::EquationModeRaw::SystemOfEquations => {
+
+            return Ok(ControlFlow::Continue(()));
+        }
+        LoopEvent::Closed => return Ok(ControlFlow::Break(())),
+    };
+
+    let response = match request {
+        RequestPackage::ListEquations => {
+            let catalogue = device.list_equations().await?;
+            messages.send(UIMessage::EquationsListed(catalogue)).await?;
+
+            return Ok(ControlFlow::Continue(()));
+        }
+        RequestPackage::FunctionPoints { payload } => {
+            let curves = device.function_points(payload).await?;
+            for (index, points) in curves.into_iter().enumerate() {
+                // system's curves are told apart by the response variant
+                let response = match index {
+                    0 => points.into(),
+                    _ => ResponsePackage::FunctionPointsSecond(points),
+                };
                 messages
-                    .send(UIMessage::ResponseReceived(
-                        request.clone(),
-                        protocol::response::FunctionPointsResponse::from(&*read_buffer_long).into(),
-                    ))
+                    .send(UIMessage::ResponseReceived(request, response))
                     .await?;
-                serial_port.read_exact(read_buffer_long).await?;
-                protocol::response::ResponsePackage::FunctionPointsSecond(
-                    protocol::response::FunctionPointsResponse::from(&*read_buffer_long),
-                )
             }
-        },
-        RequestPackage::InitialApproximations => {
-            protocol::response::InitialApproximationsResponse::from_bytes(read_buffer_short).into()
+
+            return Ok(ControlFlow::Continue(()));
         }
-        RequestPackage::ComputeRoot { .. } => {
-            Result::<ComputeRootResponse, MethodError>::from_bytes(read_buffer_short).into()
+        RequestPackage::InitialApproximations => device.initial_approximations().await?.into(),
+        RequestPackage::SetInitialApproximations { left, right } => {
+            device.set_initial_approximations(left, right).await?.into()
+        }
+        RequestPackage::ComputeRoot { payload } => device.compute_root(payload).await?.into(),
+        RequestPackage::FindAllRoots { payload } => {
+            let roots = device.find_all_roots(payload).await?;
+            messages
+                .send(UIMessage::AllRootsFound(payload.mode.into(), roots))
+                .await?;
+
+            return Ok(ControlFlow::Continue(()));
+        }
+        RequestPackage::UploadEquation { payload } => {
+            device.upload_equation(payload).await?;
+            return Ok(ControlFlow::Continue(()));
+        }
+        RequestPackage::ComputeRootTrace { payload } => {
+            let trace = device.compute_root_trace(payload).await?;
+            for record in trace.records {
+                messages
+                    .send(UIMessage::ResponseReceived(request, record.into()))
+                    .await?;
+            }
+
+            trace.result.into()
         }
     };
 
@@ -116,32 +306,105 @@ async fn loop_iteration(
         .send(UIMessage::ResponseReceived(request, response))
         .await?;
 
-    // prevent message flood
-    tokio::time::sleep(Duration::from_millis(500)).await;
-
-    Ok(())
+    Ok(ControlFlow::Continue(()))
 }
 
-async fn verify_signature(mut serial_port: SerialStream) -> io::Result<SerialStream> {
-    // if signature was not received within first 16 bytes, then connection
-    // could not be insteblished
-    let mut buffer = [0; PROTOCOL_SIGNATURE_SIZE * 2];
-
-    serial_port.read_exact(&mut buffer).await?;
-    if !is_signature_valid(&buffer) {
-        return Err(io::Error::new(
-            io::ErrorKind::ConnectionRefused,
-            "Signature is not valid. Is this device sertified by White Horizont corporation?",
-        ));
+/// Device with verified signature
+async fn connect(
+    link: &Link,
+    port_choices: &mut Receiver<String>,
+    messages: &mut Sender<UIMessage>,
+) -> Result<Device, Box<dyn Error>> {
+    let handshaking = UIMessage::ConnectionState(ConnectionState::Handshaking);
+    let PortSettings { path, baud_rate } = match link {
+        Link::SerialPort(settings) => settings,
+        Link::Simulator => {
+            let stream = Box::new(simulator::spawn());
+            messages.send(handshaking).await?;
+            return Ok(Device::handshake(stream).await?);
+        }
+    };
+    if let Some(path) = path {
+        let stream = discovery::open(path, *baud_rate)?;
+        messages.send(handshaking).await?;
+        return Ok(Device::handshake(stream).await?);
+    }
+
+    loop {
+        // every port is probed with a handshake
+        messages.send(handshaking.clone()).await?;
+        let mut ports = discovery::discover(*baud_rate).await?;
+        let responding: Vec<usize> = (0..ports.len())
+            .filter(|&index| ports[index].0.responds)
+            .collect();
+        if let [index] = responding[..] {
+            let (_, device) = ports.swap_remove(index);
+            return Ok(device.expect("responding port is connected"));
+        }
+        if ports.is_empty() {
+            return Err("no serial port is found".into());
+        }
+
+        // device may be plugged in while user has not picked anything yet
+        let rediscover = responding.is_empty().then_some(REDISCOVERY_PERIOD);
+        if let Some(device) =
+            pick_port(ports, rediscover, *baud_rate, port_choices, messages).await?
+        {
+            return Ok(device);
+        }
     }
-    serial_port
-        .write_all(&PROTOCOL_SIGNATURE.to_le_bytes())
+}
+
+/// Lets user pick any of the `ports`, responding or not.
+/// Gives up after `rediscover` period, if there is one
+async fn pick_port(
+    mut ports: Vec<(PortCandidate, Option<Device>)>,
+    rediscover: Option<Duration>,
+    baud_rate: u32,
+    port_choices: &mut Receiver<String>,
+    messages: &mut Sender<UIMessage>,
+) -> Result<Option<Device>, Box<dyn Error>> {
+    let candidates: Vec<PortCandidate> = ports
+        .iter()
+        .map(|(candidate, _)| candidate.clone())
+        .collect();
+    messages
+        .send(UIMessage::ConnectionState(ConnectionState::Connecting))
+        .await?;
+    messages
+        .send(UIMessage::PortsDiscovered(candidates))
         .await?;
-    serial_port.flush().await?;
-    // clear leftover data
-    serial_port.clear(ClearBuffer::Input)?;
+    loop {
+        let path = tokio::select! {
+            path = port_choices.next() => path.ok_or("ui is closed")?,
+            _ = tokio::time::sleep(rediscover.unwrap_or_default()), if rediscover.is_some() => {
+                return Ok(None);
+            }
+        };
+        let probed = ports
+            .iter_mut()
+            .find(|(candidate, _)| candidate.path == path)
+            .and_then(|(_, device)| device.take());
+        let device = match probed {
+            Some(device) => Ok(device),
+            // device might have been plugged in after discovery
+            None => {
+                messages
+                    .send(UIMessage::ConnectionState(ConnectionState::Handshaking))
+                    .await?;
+                Device::connect(&path, baud_rate).await
+            }
+        };
 
-    Ok(serial_port)
+        match device {
+            Ok(device) => return Ok(Some(device)),
+            Err(error) => eprintln!("{}: {}", path, error),
+        }
+        // another port may be picked
+        messages
+            .send(UIMessage::ConnectionState(ConnectionState::Connecting))
+            .await?;
+    }
 }
 
 // command has an async function which sends state to this thread
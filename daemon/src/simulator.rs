@@ -0,0 +1,283 @@
+//! Device living within daemon, so UI runs without arduino plugged in.
+//! Speaks the same protocol over an in-memory pipe, signature exchange
+//! included, and solves the same equations with the same `solvers`
+
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+
+use protocol::byte_serializable::ByteSerializable;
+use protocol::catalogue::EquationCatalogue;
+use protocol::decode_error::DecodeError;
+use protocol::device_info::DeviceInfo;
+use protocol::expression::ProgramAssembler;
+use protocol::frame::{
+    sized_payload, write_frame, FrameDecoder, FrameHeader, FrameWriter, MessageType,
+    UNSOLICITED_SEQUENCE,
+};
+use protocol::point::{Point, PointCoordinate};
+use protocol::request::compute_method::{Method, SystemMethod};
+use protocol::request::{EquationModeRaw, RequestPackage};
+use protocol::response::{InitialApproximationsResponse, TraceRecord};
+use protocol::{TNumber, LONG_PACKAGE_SIZE, PACKAGE_SIZE, POINT_AMOUNT, PROTOCOL_VERSION};
+use protocol::{PROTOCOL_SIGNATURE, PROTOCOL_SIGNATURE_SIZE};
+use solvers::system_of_equations::SystemOfEquations;
+use solvers::user_equation::UserEquation;
+use solvers::{builtin, for_each_point, is_bracket_valid, Equations, NonLinearEquation};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+/// Capacity of the pipe in each direction
+const PIPE_SIZE: usize = 1024;
+const READ_CHUNK_SIZE: usize = 256;
+
+/// Equations are plain functions, so uploaded one is shared through a static
+static USER_EQUATION: Mutex<UserEquation> = Mutex::new(UserEquation::new());
+
+/// Same equations as the firmware has
+const SINGLE: [NonLinearEquation; 3] = [
+    builtin::SQUARE_PLUS_SINE,
+    builtin::SHIFTED_LOGARITHM,
+    // slot for equation uploaded by host. Has to be the last one
+    NonLinearEquation {
+        name: solvers::user_equation::NAME,
+        function: |x| user_equation().function(x),
+        first_derivative: |x| user_equation().first_derivative(x),
+        second_derivative: Some(|x| user_equation().second_derivative(x)),
+    },
+];
+
+const SYSTEMS: [SystemOfEquations; 1] = [builtin::SINE_AND_COSINE];
+
+const EQUATIONS: Equations = Equations {
+    single: &SINGLE,
+    systems: &SYSTEMS,
+};
+
+fn user_equation() -> MutexGuard<'static, UserEquation> {
+    USER_EQUATION.lock().expect("evaluation does not panic")
+}
+
+/// Starts the device. Returned end of the pipe is what serial port is for arduino
+pub fn spawn() -> DuplexStream {
+    let (host, device) = tokio::io::duplex(PIPE_SIZE);
+    tokio::spawn(async move {
+        if let Err(error) = run(device).await {
+            eprintln!("simulated device stopped: {}", error);
+        }
+    });
+
+    host
+}
+
+fn device_info() -> DeviceInfo {
+    DeviceInfo {
+        version: PROTOCOL_VERSION,
+        methods: [
+            Method::Chord,
+            Method::Secant,
+            Method::SimpleIterationSingle,
+            Method::Newton,
+            Method::Bisection,
+        ]
+        .iter()
+        .fold(0, |mask, method| mask | method.mask()),
+        system_methods: [SystemMethod::SimpleIteration, SystemMethod::Newton]
+            .iter()
+            .fold(0, |mask, method| mask | method.mask()),
+        // there are no buttons, so there is nothing to notify about,
+        // but host does not have to poll approximations either
+        features: DeviceInfo::FEATURE_TRACE
+            | DeviceInfo::FEATURE_NOTIFICATIONS
+            | DeviceInfo::FEATURE_FIND_ALL_ROOTS
+            | DeviceInfo::FEATURE_UPLOAD_EQUATION,
+        point_amount: POINT_AMOUNT as u16,
+    }
+}
+
+/// Serves requests until host closes the pipe
+async fn run(mut stream: DuplexStream) -> io::Result<()> {
+    let signature = PROTOCOL_SIGNATURE.to_le_bytes();
+    stream.write_all(&signature).await?;
+    stream.write_all(&signature).await?;
+
+    let mut received = [0; PROTOCOL_SIGNATURE_SIZE];
+    loop {
+        stream.read_exact(&mut received).await?;
+        if received == signature {
+            break;
+        }
+    }
+
+    let mut output = Vec::new();
+    write_frame(
+        |bytes| output.extend_from_slice(bytes),
+        MessageType::Response,
+        UNSOLICITED_SEQUENCE,
+        &device_info().to_bytes(),
+    );
+    stream.write_all(&output).await?;
+
+    let mut device = SimulatedDevice {
+        approximations: InitialApproximationsResponse {
+            left: -1.,
+            right: 1.,
+        },
+        upload: ProgramAssembler::new(),
+    };
+    let mut decoder = FrameDecoder::<PACKAGE_SIZE>::new();
+    let mut buffer = [0; READ_CHUNK_SIZE];
+    loop {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+
+        output.clear();
+        let mut bytes = buffer[..read].iter().copied();
+        while let Some(result) = decoder.next_frame(|| bytes.next()) {
+            match result.and_then(|header| decode_request(&decoder, header)) {
+                Ok((sequence, request)) => device.handle(request, sequence, &mut output),
+                Err(error) => write_frame(
+                    |bytes| output.extend_from_slice(bytes),
+                    MessageType::Error,
+                    decoder.sequence(),
+                    &error.to_bytes(),
+                ),
+            }
+        }
+        stream.write_all(&output).await?;
+    }
+}
+
+fn decode_request(
+    decoder: &FrameDecoder<PACKAGE_SIZE>,
+    header: FrameHeader,
+) -> Result<(u8, RequestPackage), DecodeError> {
+    if header.message_type != MessageType::Request {
+        return Err(DecodeError::UnexpectedMessageType(
+            header.message_type.to_byte(),
+        ));
+    }
+
+    let payload = sized_payload::<PACKAGE_SIZE>(decoder.payload())?;
+    let request = RequestPackage::try_from_bytes(payload)?;
+    EQUATIONS.check_request(&request)?;
+
+    Ok((header.sequence, request))
+}
+
+struct SimulatedDevice {
+    approximations: InitialApproximationsResponse,
+    upload: ProgramAssembler,
+}
+
+impl SimulatedDevice {
+    /// Writes response frames the way firmware does
+    fn handle(&mut self, request: RequestPackage, sequence: u8, output: &mut Vec<u8>) {
+        match request {
+            RequestPackage::FunctionPoints { payload } => {
+                let mut write_points =
+                    |function: &mut dyn FnMut(TNumber) -> (TNumber, PointCoordinate)| {
+                        let mut writer = FrameWriter::new(
+                            |bytes: &[u8]| output.extend_from_slice(bytes),
+                            MessageType::Response,
+                            sequence,
+                            LONG_PACKAGE_SIZE as u16,
+                        );
+                        for_each_point(function, &mut |point: Point| {
+                            writer.write(&point.to_bytes())
+                        });
+                        writer.finish();
+                    };
+
+                match payload.mode {
+                    EquationModeRaw::SingleEquation => {
+                        let equation = &EQUATIONS.single[payload.index as usize];
+                        write_points(&mut |x| ((equation.function)(x), PointCoordinate::Y));
+                    }
+                    EquationModeRaw::SystemOfEquations => {
+                        let mut system = EQUATIONS.systems[payload.index as usize].clone();
+                        write_points(&mut system.first.function);
+                        write_points(&mut system.second.function);
+                    }
+                }
+            }
+            RequestPackage::InitialApproximations => {
+                respond(output, sequence, &self.approximations.to_bytes())
+            }
+            RequestPackage::ComputeRoot { payload } => {
+                let result = EQUATIONS.compute_root(payload, self.approximations, &mut |_| ());
+                respond(output, sequence, &result.to_bytes());
+            }
+            RequestPackage::ComputeRootTrace { payload } => {
+                // every record is a frame on its own
+                let result = EQUATIONS.compute_root(payload, self.approximations, &mut |record| {
+                    respond(output, sequence, &record.to_bytes())
+                });
+
+                respond(output, sequence, &TraceRecord::terminator().to_bytes());
+                respond(output, sequence, &result.to_bytes());
+            }
+            RequestPackage::ListEquations => write_catalogue(output, sequence),
+            RequestPackage::SetInitialApproximations { left, right } => {
+                if is_bracket_valid(left, right) {
+                    self.approximations = InitialApproximationsResponse { left, right };
+                }
+
+                respond(output, sequence, &self.approximations.to_bytes());
+            }
+            RequestPackage::FindAllRoots { payload } => {
+                // every root is a frame on its own. Empty one ends the list
+                EQUATIONS.find_all_roots(payload, &mut |root| {
+                    respond(output, sequence, &root.to_bytes())
+                });
+                respond(output, sequence, &[]);
+            }
+            RequestPackage::UploadEquation { payload } => match self.upload.push(&payload) {
+                Ok(program) => {
+                    if let Some(program) = program {
+                        user_equation().store(payload.target, program);
+                    }
+
+                    respond(output, sequence, &[]);
+                }
+                Err(error) => write_frame(
+                    |bytes| output.extend_from_slice(bytes),
+                    MessageType::Error,
+                    sequence,
+                    &error.to_bytes(),
+                ),
+            },
+        }
+    }
+}
+
+fn respond(output: &mut Vec<u8>, sequence: u8, payload: &[u8]) {
+    write_frame(
+        |bytes| output.extend_from_slice(bytes),
+        MessageType::Response,
+        sequence,
+        payload,
+    );
+}
+
+fn write_catalogue(output: &mut Vec<u8>, sequence: u8) {
+    let Equations { single, systems } = EQUATIONS;
+    let names = || {
+        single.iter().map(|equation| equation.name).chain(
+            systems
+                .iter()
+                .flat_map(|system| [system.first.name, system.second.name]),
+        )
+    };
+
+    let mut writer = FrameWriter::new(
+        |bytes: &[u8]| output.extend_from_slice(bytes),
+        MessageType::Response,
+        sequence,
+        EquationCatalogue::encoded_size(names()) as u16,
+    );
+    EquationCatalogue::encode(single.len() as u8, systems.len() as u8, names(), |bytes| {
+        writer.write(bytes)
+    });
+    writer.finish();
+}
@@ -0,0 +1,52 @@
+use iced::widget::{scrollable, Column, Row, Text};
+use iced::{Element, Length};
+use protocol::request::EquationModeRaw;
+use protocol::response::TraceRecord;
+use protocol::TNumber;
+
+use crate::UIMessage;
+
+const CELL_WIDTH: f32 = 110.;
+const TABLE_HEIGHT: f32 = 200.;
+
+/// Table with state of the method after each iteration
+pub fn view(records: &[TraceRecord], mode: EquationModeRaw) -> Element<'_, UIMessage> {
+    let header = match mode {
+        EquationModeRaw::SingleEquation => ["k", "x", "f(x)", "|x - x_prev|", "a", "b"],
+        EquationModeRaw::SystemOfEquations => ["k", "x", "y", "step", "", ""],
+    };
+
+    let rows = records.iter().enumerate().map(|(index, record)| {
+        table_row([
+            (index + 1).to_string(),
+            format_number(record.point.x),
+            format_number(record.point.y),
+            format_number(record.step),
+            format_number(record.left),
+            format_number(record.right),
+        ])
+    });
+
+    Column::new()
+        .push(table_row(header.map(String::from)))
+        .push(scrollable(Column::with_children(rows)).height(Length::Fixed(TABLE_HEIGHT)))
+        .into()
+}
+
+fn table_row(cells: [String; 6]) -> Element<'static, UIMessage> {
+    Row::with_children(
+        cells
+            .into_iter()
+            .map(|cell| Text::new(cell).width(Length::Fixed(CELL_WIDTH)).into()),
+    )
+    .into()
+}
+
+/// `NaN` stands for absent value
+fn format_number(number: TNumber) -> String {
+    if number.is_nan() {
+        return "-".to_owned();
+    }
+
+    format!("{number:.5}")
+}
@@ -1,10 +1,13 @@
+use crate::decode_error::DecodeError;
+
 // uses custom to/from methods
 // While impl TryFrom<RequestPackage> for [u8; PACKAGE_SIZE] is possible
 // package.into() is not straightforward enough
 // I'd expect person to look for `to_bytes` method
-pub trait ByteSerializable<const SIZE: usize> {
+pub trait ByteSerializable<const SIZE: usize>: Sized {
     fn to_bytes(&self) -> [u8; SIZE];
-    fn from_bytes(raw_bytes: &[u8; SIZE]) -> Self;
+    /// Bytes come from the wire and may be corrupted
+    fn try_from_bytes(raw_bytes: &[u8; SIZE]) -> Result<Self, DecodeError>;
 }
 
 pub fn read_field<const LENGTH: usize>(raw_bytes: &[u8], offset: usize) -> [u8; LENGTH] {
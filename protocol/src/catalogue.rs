@@ -0,0 +1,103 @@
+use core::str;
+
+use crate::decode_error::DecodeError;
+
+/// Names of equations flashed into arduino.
+/// Response to [`ListEquations`](crate::request::RequestPackage::ListEquations).
+///
+/// | single count | system count | names |
+/// | ------------ | ------------ | ----- |
+/// | 1            | 1            | ...   |
+///
+/// Name is `| length: u8 | utf-8 bytes |`. Names of single equations come first.
+/// Then two names per system of equations
+#[derive(Debug, Clone, Copy)]
+pub struct EquationCatalogue<'a> {
+    single_count: u8,
+    system_count: u8,
+    names: &'a [u8],
+}
+
+impl<'a> EquationCatalogue<'a> {
+    const SINGLE_COUNT_OFFSET: usize = 0;
+    const SYSTEM_COUNT_OFFSET: usize = 1;
+    const NAMES_OFFSET: usize = 2;
+
+    /// Size of the encoded catalogue. Lets arduino write frame's header
+    /// before names themselves
+    pub fn encoded_size<'n>(names: impl Iterator<Item = &'n str>) -> usize {
+        Self::NAMES_OFFSET + names.map(|name| 1 + name.len()).sum::<usize>()
+    }
+
+    /// `names` are expected in the order they are decoded.
+    /// Names longer than 255 bytes are truncated
+    pub fn encode<'n>(
+        single_count: u8,
+        system_count: u8,
+        names: impl Iterator<Item = &'n str>,
+        mut write: impl FnMut(&[u8]),
+    ) {
+        write(&[single_count, system_count]);
+        for name in names {
+            let name = &name.as_bytes()[..name.len().min(u8::MAX as usize)];
+            write(&[name.len() as u8]);
+            write(name);
+        }
+    }
+
+    pub fn try_from_bytes(raw_bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        if raw_bytes.len() < Self::NAMES_OFFSET {
+            return Err(DecodeError::UnexpectedLength);
+        }
+
+        let catalogue = Self {
+            single_count: raw_bytes[Self::SINGLE_COUNT_OFFSET],
+            system_count: raw_bytes[Self::SYSTEM_COUNT_OFFSET],
+            names: &raw_bytes[Self::NAMES_OFFSET..],
+        };
+
+        // validate once, so iterators do not have to
+        let expected = catalogue.single_count as usize + 2 * catalogue.system_count as usize;
+        let mut rest = catalogue.names;
+        for _ in 0..expected {
+            let (name, tail) = split_name(rest).ok_or(DecodeError::UnexpectedLength)?;
+            str::from_utf8(name).map_err(|_| DecodeError::InvalidString)?;
+            rest = tail;
+        }
+
+        if !rest.is_empty() {
+            return Err(DecodeError::UnexpectedLength);
+        }
+
+        Ok(catalogue)
+    }
+
+    pub fn single(&self) -> impl Iterator<Item = &'a str> {
+        self.names().take(self.single_count as usize)
+    }
+
+    /// Names of the first and the second equation of every system
+    pub fn systems(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let mut names = self.names().skip(self.single_count as usize);
+        core::iter::from_fn(move || Some((names.next()?, names.next()?)))
+    }
+
+    fn names(&self) -> impl Iterator<Item = &'a str> {
+        let mut rest = self.names;
+        core::iter::from_fn(move || {
+            let (name, tail) = split_name(rest)?;
+            rest = tail;
+            Some(str::from_utf8(name).expect("validated upon decoding"))
+        })
+    }
+}
+
+/// Splits length prefixed name from the rest of the bytes
+fn split_name(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&length, rest) = bytes.split_first()?;
+    if rest.len() < length as usize {
+        return None;
+    }
+
+    Some(rest.split_at(length as usize))
+}
@@ -0,0 +1,135 @@
+use core::fmt::Display;
+
+/// Package is either broken or contains value which does not correspond
+/// to any known variant. `Unknown*` variants hold the offending byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    UnknownRequestType(u8),
+    UnknownEquationMode(u8),
+    UnknownMethod(u8),
+    UnknownSystemMethod(u8),
+    UnknownMethodError(u8),
+    UnknownMessageType(u8),
+    UnknownNotification(u8),
+    UnknownStoppingCriterion(u8),
+    UnknownInstruction(u8),
+    UnknownProgramTarget(u8),
+    /// Message type is known, but is not expected by the receiver
+    UnexpectedMessageType(u8),
+    ChecksumMismatch,
+    /// Frame does not fit into receiver's buffer
+    PayloadTooLong,
+    /// Payload size does not match the message
+    UnexpectedLength,
+    /// Text is not valid utf-8
+    InvalidString,
+    /// Uploaded program would not evaluate to a single value or chunks arrived out of order
+    InvalidProgram,
+    /// Index of single equation is out of catalogue
+    UnknownEquation(u8),
+    /// Index of system of equations is out of catalogue
+    UnknownSystem(u8),
+}
+
+impl DecodeError {
+    const UNKNOWN_REQUEST_TYPE: u8 = 0;
+    const UNKNOWN_EQUATION_MODE: u8 = 1;
+    const UNKNOWN_METHOD: u8 = 2;
+    const UNKNOWN_SYSTEM_METHOD: u8 = 3;
+    const UNKNOWN_METHOD_ERROR: u8 = 4;
+    const UNKNOWN_MESSAGE_TYPE: u8 = 5;
+    const UNEXPECTED_MESSAGE_TYPE: u8 = 6;
+    const CHECKSUM_MISMATCH: u8 = 7;
+    const PAYLOAD_TOO_LONG: u8 = 8;
+    const UNEXPECTED_LENGTH: u8 = 9;
+    const INVALID_STRING: u8 = 10;
+    const UNKNOWN_NOTIFICATION: u8 = 11;
+    const UNKNOWN_STOPPING_CRITERION: u8 = 12;
+    const UNKNOWN_INSTRUCTION: u8 = 13;
+    const INVALID_PROGRAM: u8 = 14;
+    const UNKNOWN_PROGRAM_TARGET: u8 = 15;
+    const UNKNOWN_EQUATION: u8 = 16;
+    const UNKNOWN_SYSTEM: u8 = 17;
+
+    pub const DECODE_ERROR_SIZE: usize = 2;
+
+    pub fn to_bytes(&self) -> [u8; Self::DECODE_ERROR_SIZE] {
+        match *self {
+            DecodeError::UnknownRequestType(byte) => [Self::UNKNOWN_REQUEST_TYPE, byte],
+            DecodeError::UnknownEquationMode(byte) => [Self::UNKNOWN_EQUATION_MODE, byte],
+            DecodeError::UnknownMethod(byte) => [Self::UNKNOWN_METHOD, byte],
+            DecodeError::UnknownSystemMethod(byte) => [Self::UNKNOWN_SYSTEM_METHOD, byte],
+            DecodeError::UnknownMethodError(byte) => [Self::UNKNOWN_METHOD_ERROR, byte],
+            DecodeError::UnknownMessageType(byte) => [Self::UNKNOWN_MESSAGE_TYPE, byte],
+            DecodeError::UnexpectedMessageType(byte) => [Self::UNEXPECTED_MESSAGE_TYPE, byte],
+            DecodeError::ChecksumMismatch => [Self::CHECKSUM_MISMATCH, 0],
+            DecodeError::PayloadTooLong => [Self::PAYLOAD_TOO_LONG, 0],
+            DecodeError::UnexpectedLength => [Self::UNEXPECTED_LENGTH, 0],
+            DecodeError::InvalidString => [Self::INVALID_STRING, 0],
+            DecodeError::UnknownNotification(byte) => [Self::UNKNOWN_NOTIFICATION, byte],
+            DecodeError::UnknownStoppingCriterion(byte) => [Self::UNKNOWN_STOPPING_CRITERION, byte],
+            DecodeError::UnknownInstruction(byte) => [Self::UNKNOWN_INSTRUCTION, byte],
+            DecodeError::InvalidProgram => [Self::INVALID_PROGRAM, 0],
+            DecodeError::UnknownProgramTarget(byte) => [Self::UNKNOWN_PROGRAM_TARGET, byte],
+            DecodeError::UnknownEquation(byte) => [Self::UNKNOWN_EQUATION, byte],
+            DecodeError::UnknownSystem(byte) => [Self::UNKNOWN_SYSTEM, byte],
+        }
+    }
+
+    /// `None` if kind is unknown as well
+    pub fn from_bytes(raw_bytes: &[u8; Self::DECODE_ERROR_SIZE]) -> Option<Self> {
+        let [kind, byte] = *raw_bytes;
+        match kind {
+            Self::UNKNOWN_REQUEST_TYPE => Some(DecodeError::UnknownRequestType(byte)),
+            Self::UNKNOWN_EQUATION_MODE => Some(DecodeError::UnknownEquationMode(byte)),
+            Self::UNKNOWN_METHOD => Some(DecodeError::UnknownMethod(byte)),
+            Self::UNKNOWN_SYSTEM_METHOD => Some(DecodeError::UnknownSystemMethod(byte)),
+            Self::UNKNOWN_METHOD_ERROR => Some(DecodeError::UnknownMethodError(byte)),
+            Self::UNKNOWN_MESSAGE_TYPE => Some(DecodeError::UnknownMessageType(byte)),
+            Self::UNEXPECTED_MESSAGE_TYPE => Some(DecodeError::UnexpectedMessageType(byte)),
+            Self::CHECKSUM_MISMATCH => Some(DecodeError::ChecksumMismatch),
+            Self::PAYLOAD_TOO_LONG => Some(DecodeError::PayloadTooLong),
+            Self::UNEXPECTED_LENGTH => Some(DecodeError::UnexpectedLength),
+            Self::INVALID_STRING => Some(DecodeError::InvalidString),
+            Self::UNKNOWN_NOTIFICATION => Some(DecodeError::UnknownNotification(byte)),
+            Self::UNKNOWN_STOPPING_CRITERION => Some(DecodeError::UnknownStoppingCriterion(byte)),
+            Self::UNKNOWN_INSTRUCTION => Some(DecodeError::UnknownInstruction(byte)),
+            Self::INVALID_PROGRAM => Some(DecodeError::InvalidProgram),
+            Self::UNKNOWN_PROGRAM_TARGET => Some(DecodeError::UnknownProgramTarget(byte)),
+            Self::UNKNOWN_EQUATION => Some(DecodeError::UnknownEquation(byte)),
+            Self::UNKNOWN_SYSTEM => Some(DecodeError::UnknownSystem(byte)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::UnknownRequestType(byte) => write!(f, "Unknown request type {byte}"),
+            DecodeError::UnknownEquationMode(byte) => write!(f, "Unknown equation mode {byte}"),
+            DecodeError::UnknownMethod(byte) => write!(f, "Unknown method {byte}"),
+            DecodeError::UnknownSystemMethod(byte) => write!(f, "Unknown system method {byte}"),
+            DecodeError::UnknownMethodError(byte) => write!(f, "Unknown method error {byte}"),
+            DecodeError::UnknownMessageType(byte) => write!(f, "Unknown message type {byte}"),
+            DecodeError::UnexpectedMessageType(byte) => {
+                write!(f, "Unexpected message type {byte}")
+            }
+            DecodeError::ChecksumMismatch => write!(f, "Checksum mismatch"),
+            DecodeError::PayloadTooLong => write!(f, "Payload is too long"),
+            DecodeError::UnexpectedLength => write!(f, "Payload length does not match message"),
+            DecodeError::InvalidString => write!(f, "Text is not valid utf-8"),
+            DecodeError::UnknownNotification(byte) => write!(f, "Unknown notification {byte}"),
+            DecodeError::UnknownStoppingCriterion(byte) => {
+                write!(f, "Unknown stopping criterion {byte}")
+            }
+            DecodeError::UnknownInstruction(byte) => write!(f, "Unknown instruction {byte}"),
+            DecodeError::InvalidProgram => write!(f, "Program is invalid"),
+            DecodeError::UnknownProgramTarget(byte) => write!(f, "Unknown program target {byte}"),
+            DecodeError::UnknownEquation(byte) => write!(f, "Unknown equation {byte}"),
+            DecodeError::UnknownSystem(byte) => write!(f, "Unknown system of equations {byte}"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
@@ -0,0 +1,93 @@
+use crate::byte_serializable::{read_field, ByteSerializable};
+use crate::decode_error::DecodeError;
+use crate::request::compute_method::{Method, SystemMethod};
+use crate::{POINT_AMOUNT, PROTOCOL_VERSION};
+
+/// Sent by arduino once signature is verified.
+/// Describes what flashed firmware is capable of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub version: u8,
+    /// Bit per [`Method`]. See [`Method::mask`]
+    pub methods: u8,
+    /// Bit per [`SystemMethod`]. See [`SystemMethod::mask`]
+    pub system_methods: u8,
+    /// `FEATURE_*` bits
+    pub features: u8,
+    /// Points per function in
+    /// [`FunctionPointsResponse`](crate::response::FunctionPointsResponse)
+    pub point_amount: u16,
+}
+
+impl DeviceInfo {
+    pub const DEVICE_INFO_SIZE: usize = 6;
+
+    /// [`ComputeRootTrace`](crate::request::RequestPackage::ComputeRootTrace) is supported
+    pub const FEATURE_TRACE: u8 = 1 << 0;
+    /// Arduino sends [`Notification`](crate::notification::Notification)s.
+    /// Otherwise host has to poll
+    pub const FEATURE_NOTIFICATIONS: u8 = 1 << 1;
+    /// [`FindAllRoots`](crate::request::RequestPackage::FindAllRoots) is supported
+    pub const FEATURE_FIND_ALL_ROOTS: u8 = 1 << 2;
+    /// [`UploadEquation`](crate::request::RequestPackage::UploadEquation) is supported.
+    /// The last single equation is the user slot
+    pub const FEATURE_UPLOAD_EQUATION: u8 = 1 << 3;
+
+    const VERSION_OFFSET: usize = 0;
+    const METHODS_OFFSET: usize = 1;
+    const SYSTEM_METHODS_OFFSET: usize = 2;
+    const FEATURES_OFFSET: usize = 3;
+    const POINT_AMOUNT_OFFSET: usize = 4;
+
+    /// Whether host is able to talk to the device at all
+    pub fn is_compatible(&self) -> bool {
+        self.version == PROTOCOL_VERSION && self.point_amount as usize == POINT_AMOUNT
+    }
+
+    pub fn supports_method(&self, method: Method) -> bool {
+        self.methods & method.mask() != 0
+    }
+
+    pub fn supports_system_method(&self, method: SystemMethod) -> bool {
+        self.system_methods & method.mask() != 0
+    }
+
+    pub fn supports_trace(&self) -> bool {
+        self.features & Self::FEATURE_TRACE != 0
+    }
+
+    pub fn supports_notifications(&self) -> bool {
+        self.features & Self::FEATURE_NOTIFICATIONS != 0
+    }
+
+    pub fn supports_find_all_roots(&self) -> bool {
+        self.features & Self::FEATURE_FIND_ALL_ROOTS != 0
+    }
+
+    pub fn supports_upload_equation(&self) -> bool {
+        self.features & Self::FEATURE_UPLOAD_EQUATION != 0
+    }
+}
+
+impl ByteSerializable<{ Self::DEVICE_INFO_SIZE }> for DeviceInfo {
+    fn to_bytes(&self) -> [u8; Self::DEVICE_INFO_SIZE] {
+        let mut bytes = [0; Self::DEVICE_INFO_SIZE];
+        bytes[Self::VERSION_OFFSET] = self.version;
+        bytes[Self::METHODS_OFFSET] = self.methods;
+        bytes[Self::SYSTEM_METHODS_OFFSET] = self.system_methods;
+        bytes[Self::FEATURES_OFFSET] = self.features;
+        bytes[Self::POINT_AMOUNT_OFFSET..].copy_from_slice(&self.point_amount.to_le_bytes());
+
+        bytes
+    }
+
+    fn try_from_bytes(raw_bytes: &[u8; Self::DEVICE_INFO_SIZE]) -> Result<Self, DecodeError> {
+        Ok(Self {
+            version: raw_bytes[Self::VERSION_OFFSET],
+            methods: raw_bytes[Self::METHODS_OFFSET],
+            system_methods: raw_bytes[Self::SYSTEM_METHODS_OFFSET],
+            features: raw_bytes[Self::FEATURES_OFFSET],
+            point_amount: u16::from_le_bytes(read_field(raw_bytes, Self::POINT_AMOUNT_OFFSET)),
+        })
+    }
+}
@@ -0,0 +1,481 @@
+//! Bytecode of equations defined at runtime.
+//!
+//! Program is a sequence of instructions for a stack machine in postfix order:
+//! `x^3 - 1` is `X, Constant(3), Pow, Constant(1), Subtract`.
+//! Every instruction is a single byte, except for [`Instruction::Constant`]
+//! which is followed by `TNumber`. Valid program leaves exactly one value on the stack.
+//!
+//! Program is uploaded in [`UploadChunk`](crate::request::payloads::UploadChunk)s,
+//! as it does not fit into a single request package. Host may upload derivatives
+//! of the equation as separate programs, see [`ProgramTarget`].
+
+use crate::decode_error::DecodeError;
+use crate::request::payloads::UploadChunk;
+use crate::{TNumber, T_NUMBER_SIZE_BYTES};
+
+/// Arduino keeps program in RAM. Longer programs are rejected
+pub const MAX_PROGRAM_SIZE: usize = 64;
+/// Evaluation never needs more room than that
+pub const MAX_STACK_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Constant(TNumber),
+    X,
+    /// Reserved for systems of equations. Single equations see `0`
+    Y,
+    Add,
+    /// Second value is subtracted from the first one
+    Subtract,
+    Multiply,
+    /// First value is divided by the second one
+    Divide,
+    /// First value raised to the power of the second one
+    Pow,
+    Negate,
+    Sin,
+    Cos,
+    /// Natural logarithm
+    Ln,
+    Exp,
+}
+
+impl Instruction {
+    const CONSTANT: u8 = 0;
+    const VARIABLE_X: u8 = 1;
+    const VARIABLE_Y: u8 = 2;
+    const ADD: u8 = 3;
+    const SUBTRACT: u8 = 4;
+    const MULTIPLY: u8 = 5;
+    const DIVIDE: u8 = 6;
+    const POW: u8 = 7;
+    const NEGATE: u8 = 8;
+    const SIN: u8 = 9;
+    const COS: u8 = 10;
+    const LN: u8 = 11;
+    const EXP: u8 = 12;
+
+    /// Values taken from the stack. Result is pushed back
+    pub fn arity(&self) -> usize {
+        match self {
+            Instruction::Constant(_) | Instruction::X | Instruction::Y => 0,
+            Instruction::Negate
+            | Instruction::Sin
+            | Instruction::Cos
+            | Instruction::Ln
+            | Instruction::Exp => 1,
+            Instruction::Add
+            | Instruction::Subtract
+            | Instruction::Multiply
+            | Instruction::Divide
+            | Instruction::Pow => 2,
+        }
+    }
+
+    pub fn encoded_size(&self) -> usize {
+        match self {
+            Instruction::Constant(_) => 1 + T_NUMBER_SIZE_BYTES,
+            _ => 1,
+        }
+    }
+
+    pub fn encode(&self, mut write: impl FnMut(&[u8])) {
+        let opcode = match self {
+            Instruction::Constant(_) => Self::CONSTANT,
+            Instruction::X => Self::VARIABLE_X,
+            Instruction::Y => Self::VARIABLE_Y,
+            Instruction::Add => Self::ADD,
+            Instruction::Subtract => Self::SUBTRACT,
+            Instruction::Multiply => Self::MULTIPLY,
+            Instruction::Divide => Self::DIVIDE,
+            Instruction::Pow => Self::POW,
+            Instruction::Negate => Self::NEGATE,
+            Instruction::Sin => Self::SIN,
+            Instruction::Cos => Self::COS,
+            Instruction::Ln => Self::LN,
+            Instruction::Exp => Self::EXP,
+        };
+
+        write(&[opcode]);
+        if let Instruction::Constant(value) = self {
+            write(&value.to_le_bytes());
+        }
+    }
+
+    /// Instruction at the start of `bytes` and the rest of them
+    pub fn decode(bytes: &[u8]) -> Result<(Self, &[u8]), DecodeError> {
+        let (&opcode, rest) = bytes.split_first().ok_or(DecodeError::InvalidProgram)?;
+        let instruction = match opcode {
+            Self::CONSTANT => {
+                if rest.len() < T_NUMBER_SIZE_BYTES {
+                    return Err(DecodeError::InvalidProgram);
+                }
+
+                let (value, rest) = rest.split_at(T_NUMBER_SIZE_BYTES);
+                let value = TNumber::from_le_bytes(value.try_into().expect("lengthes match"));
+                return Ok((Instruction::Constant(value), rest));
+            }
+            Self::VARIABLE_X => Instruction::X,
+            Self::VARIABLE_Y => Instruction::Y,
+            Self::ADD => Instruction::Add,
+            Self::SUBTRACT => Instruction::Subtract,
+            Self::MULTIPLY => Instruction::Multiply,
+            Self::DIVIDE => Instruction::Divide,
+            Self::POW => Instruction::Pow,
+            Self::NEGATE => Instruction::Negate,
+            Self::SIN => Instruction::Sin,
+            Self::COS => Instruction::Cos,
+            Self::LN => Instruction::Ln,
+            Self::EXP => Instruction::Exp,
+            _ => return Err(DecodeError::UnknownInstruction(opcode)),
+        };
+
+        Ok((instruction, rest))
+    }
+}
+
+/// Which part of the user equation program defines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramTarget {
+    /// Replaces the equation. Derivatives uploaded before are dropped
+    Function,
+    FirstDerivative,
+    SecondDerivative,
+}
+
+impl ProgramTarget {
+    const FUNCTION: u8 = 0;
+    const FIRST_DERIVATIVE: u8 = 1;
+    const SECOND_DERIVATIVE: u8 = 2;
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            ProgramTarget::Function => ProgramTarget::FUNCTION,
+            ProgramTarget::FirstDerivative => ProgramTarget::FIRST_DERIVATIVE,
+            ProgramTarget::SecondDerivative => ProgramTarget::SECOND_DERIVATIVE,
+        }
+    }
+}
+
+impl TryFrom<u8> for ProgramTarget {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            ProgramTarget::FUNCTION => Ok(ProgramTarget::Function),
+            ProgramTarget::FIRST_DERIVATIVE => Ok(ProgramTarget::FirstDerivative),
+            ProgramTarget::SECOND_DERIVATIVE => Ok(ProgramTarget::SecondDerivative),
+            _ => Err(DecodeError::UnknownProgramTarget(byte)),
+        }
+    }
+}
+
+/// Validated bytecode
+#[derive(Debug, Clone, Copy)]
+pub struct Program {
+    bytes: [u8; MAX_PROGRAM_SIZE],
+    length: u8,
+}
+
+impl Program {
+    /// Evaluates to nothing. Slot holds it until anything is uploaded
+    pub const fn empty() -> Self {
+        Self {
+            bytes: [0; MAX_PROGRAM_SIZE],
+            length: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Size of the encoded program. Does not check whether it fits
+    pub fn encoded_size(instructions: &[Instruction]) -> usize {
+        instructions.iter().map(Instruction::encoded_size).sum()
+    }
+
+    pub fn encode(instructions: &[Instruction], mut write: impl FnMut(&[u8])) {
+        for instruction in instructions {
+            instruction.encode(&mut write);
+        }
+    }
+
+    /// Checks that every instruction is known, stack neither underflows nor
+    /// exceeds [`MAX_STACK_DEPTH`] and exactly one value is left
+    pub fn try_from_bytes(raw_bytes: &[u8]) -> Result<Self, DecodeError> {
+        if raw_bytes.len() > MAX_PROGRAM_SIZE {
+            return Err(DecodeError::PayloadTooLong);
+        }
+
+        let mut depth: usize = 0;
+        let mut rest = raw_bytes;
+        while !rest.is_empty() {
+            let (instruction, tail) = Instruction::decode(rest)?;
+            depth = match depth.checked_sub(instruction.arity()) {
+                Some(remaining) if remaining < MAX_STACK_DEPTH => remaining + 1,
+                _ => return Err(DecodeError::InvalidProgram),
+            };
+            rest = tail;
+        }
+
+        if depth != 1 {
+            return Err(DecodeError::InvalidProgram);
+        }
+
+        let mut bytes = [0; MAX_PROGRAM_SIZE];
+        bytes[..raw_bytes.len()].copy_from_slice(raw_bytes);
+        Ok(Self {
+            bytes,
+            length: raw_bytes.len() as u8,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length as usize]
+    }
+
+    pub fn instructions(&self) -> impl Iterator<Item = Instruction> + '_ {
+        let mut rest = self.as_bytes();
+        core::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+
+            let (instruction, tail) = Instruction::decode(rest).expect("validated upon decoding");
+            rest = tail;
+            Some(instruction)
+        })
+    }
+}
+
+/// Collects uploaded chunks into a program
+pub struct ProgramAssembler {
+    bytes: [u8; MAX_PROGRAM_SIZE],
+    received: usize,
+    target: ProgramTarget,
+}
+
+impl ProgramAssembler {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; MAX_PROGRAM_SIZE],
+            received: 0,
+            target: ProgramTarget::Function,
+        }
+    }
+
+    /// Program once the last chunk is received. It is meant for the `target`
+    /// of that chunk. Chunk which starts from the beginning starts upload over
+    pub fn push(&mut self, chunk: &UploadChunk) -> Result<Option<Program>, DecodeError> {
+        let start = chunk.start as usize;
+        let length = chunk.length as usize;
+        if length > MAX_PROGRAM_SIZE {
+            return Err(DecodeError::PayloadTooLong);
+        }
+
+        if start == 0 {
+            self.received = 0;
+            self.target = chunk.target;
+        }
+
+        // chunk is lost, repeated or belongs to another program
+        if start != self.received || start >= length || chunk.target != self.target {
+            self.received = 0;
+            return Err(DecodeError::InvalidProgram);
+        }
+
+        let end = length.min(start + UploadChunk::CHUNK_SIZE);
+        self.bytes[start..end].copy_from_slice(&chunk.bytes[..end - start]);
+        self.received = end;
+        if end < length {
+            return Ok(None);
+        }
+
+        self.received = 0;
+        Program::try_from_bytes(&self.bytes[..length]).map(Some)
+    }
+}
+
+impl Default for ProgramAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// `x^3 - x - 1`
+    const CUBIC: [Instruction; 7] = [
+        Instruction::X,
+        Instruction::Constant(3.),
+        Instruction::Pow,
+        Instruction::X,
+        Instruction::Subtract,
+        Instruction::Constant(1.),
+        Instruction::Subtract,
+    ];
+
+    fn encode(instructions: &[Instruction]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        Program::encode(instructions, |chunk| bytes.extend_from_slice(chunk));
+        bytes
+    }
+
+    fn program(instructions: &[Instruction]) -> Program {
+        Program::try_from_bytes(&encode(instructions)).unwrap()
+    }
+
+    /// Adds up `amount` of `x`es pushed onto the stack at once
+    fn sum_of_x(amount: usize) -> Vec<Instruction> {
+        let mut instructions = std::vec![Instruction::X; amount];
+        instructions.extend(std::iter::repeat_n(Instruction::Add, amount - 1));
+        instructions
+    }
+
+    fn upload(assembler: &mut ProgramAssembler, chunks: &[UploadChunk]) -> Vec<Program> {
+        chunks
+            .iter()
+            .filter_map(|chunk| assembler.push(chunk).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn instructions_survive_encoding() {
+        let program = program(&CUBIC);
+
+        assert_eq!(program.as_bytes().len(), Program::encoded_size(&CUBIC));
+        assert!(program.instructions().eq(CUBIC));
+    }
+
+    #[test]
+    fn unknown_opcode_is_rejected() {
+        let result = Program::try_from_bytes(&[0xff]);
+
+        assert!(matches!(result, Err(DecodeError::UnknownInstruction(0xff))));
+    }
+
+    #[test]
+    fn truncated_constant_is_rejected() {
+        let bytes = encode(&[Instruction::Constant(1.)]);
+
+        let result = Program::try_from_bytes(&bytes[..bytes.len() - 1]);
+
+        assert!(matches!(result, Err(DecodeError::InvalidProgram)));
+    }
+
+    #[test]
+    fn stack_underflow_is_rejected() {
+        let bytes = encode(&[Instruction::X, Instruction::Add]);
+
+        assert!(matches!(
+            Program::try_from_bytes(&bytes),
+            Err(DecodeError::InvalidProgram)
+        ));
+    }
+
+    #[test]
+    fn stack_deeper_than_limit_is_rejected() {
+        let deepest = encode(&sum_of_x(MAX_STACK_DEPTH));
+        let too_deep = encode(&sum_of_x(MAX_STACK_DEPTH + 1));
+
+        assert!(Program::try_from_bytes(&deepest).is_ok());
+        assert!(matches!(
+            Program::try_from_bytes(&too_deep),
+            Err(DecodeError::InvalidProgram)
+        ));
+    }
+
+    #[test]
+    fn program_has_to_leave_single_value() {
+        let nothing = Program::try_from_bytes(&[]);
+        let two_values = Program::try_from_bytes(&encode(&[Instruction::X, Instruction::Y]));
+
+        assert!(matches!(nothing, Err(DecodeError::InvalidProgram)));
+        assert!(matches!(two_values, Err(DecodeError::InvalidProgram)));
+    }
+
+    #[test]
+    fn program_longer_than_limit_is_rejected() {
+        let result = Program::try_from_bytes(&[Instruction::VARIABLE_X; MAX_PROGRAM_SIZE + 1]);
+
+        assert!(matches!(result, Err(DecodeError::PayloadTooLong)));
+    }
+
+    #[test]
+    fn chunks_are_assembled_in_order() {
+        let program = program(&CUBIC);
+        let chunks: Vec<UploadChunk> =
+            UploadChunk::split(ProgramTarget::FirstDerivative, &program).collect();
+        let mut assembler = ProgramAssembler::new();
+
+        let assembled = upload(&mut assembler, &chunks);
+
+        assert!(chunks.len() > 1, "program should not fit into one chunk");
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.target == ProgramTarget::FirstDerivative));
+        let [assembled] = &assembled[..] else {
+            panic!("expected single program, got {}", assembled.len());
+        };
+        assert_eq!(assembled.as_bytes(), program.as_bytes());
+    }
+
+    #[test]
+    fn chunk_out_of_order_is_rejected() {
+        let program = program(&CUBIC);
+        let chunks: Vec<UploadChunk> =
+            UploadChunk::split(ProgramTarget::Function, &program).collect();
+        let mut assembler = ProgramAssembler::new();
+
+        let skipped = assembler.push(&chunks[1]);
+
+        assert!(matches!(skipped, Err(DecodeError::InvalidProgram)));
+    }
+
+    #[test]
+    fn repeated_chunk_breaks_upload() {
+        // `1 + 2 + 3 + 4 + 5` takes three chunks
+        let mut instructions = std::vec![Instruction::Constant(1.)];
+        for value in 2..=5 {
+            instructions.extend([Instruction::Constant(value as TNumber), Instruction::Add]);
+        }
+        let program = program(&instructions);
+        let chunks: Vec<UploadChunk> =
+            UploadChunk::split(ProgramTarget::Function, &program).collect();
+        let mut assembler = ProgramAssembler::new();
+
+        assert_eq!(chunks.len(), 3);
+        assert!(matches!(assembler.push(&chunks[0]), Ok(None)));
+        assert!(matches!(assembler.push(&chunks[1]), Ok(None)));
+        assert!(matches!(
+            assembler.push(&chunks[1]),
+            Err(DecodeError::InvalidProgram)
+        ));
+        // upload starts over from the first chunk
+        assert_eq!(upload(&mut assembler, &chunks).len(), 1);
+    }
+
+    #[test]
+    fn chunk_of_another_target_is_rejected() {
+        let program = program(&CUBIC);
+        let function: Vec<UploadChunk> =
+            UploadChunk::split(ProgramTarget::Function, &program).collect();
+        let derivative: Vec<UploadChunk> =
+            UploadChunk::split(ProgramTarget::FirstDerivative, &program).collect();
+        let mut assembler = ProgramAssembler::new();
+
+        assembler.push(&function[0]).unwrap();
+
+        assert!(matches!(
+            assembler.push(&derivative[1]),
+            Err(DecodeError::InvalidProgram)
+        ));
+    }
+}
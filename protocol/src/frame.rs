@@ -0,0 +1,634 @@
+//! Every request and response travels inside of a frame:
+//!
+//! | start | message type | sequence | length | payload  | checksum |
+//! | ----- | ------------ | -------- | ------ | -------- | -------- |
+//! | 2     | 1            | 1        | 2      | `length` | 2        |
+//!
+//! Checksum is CRC-16 of everything between start marker and checksum.
+//! Frames are written and read byte by byte, so neither side has to keep
+//! the whole frame in memory while sending it.
+
+use crate::decode_error::DecodeError;
+
+pub const FRAME_START: [u8; 2] = [0xa5, 0x5a];
+/// Sequence number of frames which do not answer any request:
+/// [`DeviceInfo`](crate::device_info::DeviceInfo) sent right after signature
+/// is verified and [notifications](MessageType::Notification)
+pub const UNSOLICITED_SEQUENCE: u8 = 0;
+
+const MESSAGE_TYPE_OFFSET: usize = 0;
+const SEQUENCE_OFFSET: usize = 1;
+const LENGTH_OFFSET: usize = 2;
+/// Header without start marker
+const HEADER_FIELDS_SIZE: usize = LENGTH_OFFSET + size_of::<u16>();
+const CHECKSUM_SIZE: usize = size_of::<u16>();
+
+pub const FRAME_OVERHEAD: usize = FRAME_START.len() + HEADER_FIELDS_SIZE + CHECKSUM_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    /// Host to arduino
+    Request,
+    /// Arduino to host. Answers request with the same sequence number
+    Response,
+    /// Arduino to host. Request with the same sequence number could not be decoded.
+    /// Payload is [`DecodeError`]
+    Error,
+    /// Arduino to host. Sent on arduino's own between responses.
+    /// Payload is [`Notification`](crate::notification::Notification)
+    Notification,
+}
+
+impl MessageType {
+    const REQUEST: u8 = 0;
+    const RESPONSE: u8 = 1;
+    const ERROR: u8 = 2;
+    const NOTIFICATION: u8 = 3;
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MessageType::Request => MessageType::REQUEST,
+            MessageType::Response => MessageType::RESPONSE,
+            MessageType::Error => MessageType::ERROR,
+            MessageType::Notification => MessageType::NOTIFICATION,
+        }
+    }
+}
+
+impl TryFrom<u8> for MessageType {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            MessageType::REQUEST => Ok(MessageType::Request),
+            MessageType::RESPONSE => Ok(MessageType::Response),
+            MessageType::ERROR => Ok(MessageType::Error),
+            MessageType::NOTIFICATION => Ok(MessageType::Notification),
+            _ => Err(DecodeError::UnknownMessageType(byte)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameHeader {
+    pub message_type: MessageType,
+    pub sequence: u8,
+    pub length: u16,
+}
+
+/// CRC-16/CCITT-FALSE. Computed bit by bit: lookup table does not fit
+/// into arduino's memory comfortably
+#[derive(Debug, Clone, Copy)]
+pub struct Crc16(u16);
+
+impl Crc16 {
+    const POLYNOMIAL: u16 = 0x1021;
+
+    pub const fn new() -> Self {
+        Self(0xffff)
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                self.0 = match self.0 & 0x8000 {
+                    0 => self.0 << 1,
+                    _ => (self.0 << 1) ^ Self::POLYNOMIAL,
+                };
+            }
+        }
+    }
+
+    pub fn finish(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Default for Crc16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes frame piece by piece. Payload of exactly `length` bytes
+/// must be written before [`finish`](FrameWriter::finish)
+pub struct FrameWriter<W: FnMut(&[u8])> {
+    write: W,
+    crc: Crc16,
+}
+
+impl<W: FnMut(&[u8])> FrameWriter<W> {
+    /// Writes header right away
+    pub fn new(mut write: W, message_type: MessageType, sequence: u8, length: u16) -> Self {
+        let mut header = [0; HEADER_FIELDS_SIZE];
+        header[MESSAGE_TYPE_OFFSET] = message_type.to_byte();
+        header[SEQUENCE_OFFSET] = sequence;
+        header[LENGTH_OFFSET..].copy_from_slice(&length.to_le_bytes());
+
+        let mut crc = Crc16::new();
+        crc.update(&header);
+
+        write(&FRAME_START);
+        write(&header);
+
+        Self { write, crc }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.crc.update(bytes);
+        (self.write)(bytes);
+    }
+
+    /// Writes checksum
+    pub fn finish(mut self) {
+        (self.write)(&self.crc.finish().to_le_bytes());
+    }
+}
+
+/// Writes frame with payload known in advance
+pub fn write_frame(
+    write: impl FnMut(&[u8]),
+    message_type: MessageType,
+    sequence: u8,
+    payload: &[u8],
+) {
+    let mut writer = FrameWriter::new(write, message_type, sequence, payload.len() as u16);
+    writer.write(payload);
+    writer.finish();
+}
+
+/// Payload of the exact size
+pub fn sized_payload<const SIZE: usize>(payload: &[u8]) -> Result<&[u8; SIZE], DecodeError> {
+    payload
+        .try_into()
+        .map_err(|_| DecodeError::UnexpectedLength)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DecoderState {
+    /// Looking for start marker. Everything else is noise
+    Start,
+    Header,
+    Payload,
+    Checksum,
+}
+
+/// Bytes of the last broken frame, which follow its start marker.
+/// Corrupted length may swallow frames after it, so they are scanned again
+struct Rescan {
+    /// Header and checksum are copied aside. Payload stays in place: frames
+    /// found in it are moved only towards its beginning
+    header: [u8; HEADER_FIELDS_SIZE],
+    checksum: [u8; CHECKSUM_SIZE],
+    payload_length: usize,
+    size: usize,
+    /// The next byte to look for start marker at
+    position: usize,
+}
+
+/// Assembles frames from bytes as they arrive.
+/// Skips noise and broken frames, so the stream recovers after
+/// lost or extra bytes
+pub struct FrameDecoder<const CAPACITY: usize> {
+    state: DecoderState,
+    received: usize,
+    header: [u8; HEADER_FIELDS_SIZE],
+    length: usize,
+    payload: [u8; CAPACITY],
+    checksum: [u8; CHECKSUM_SIZE],
+    rescan: Rescan,
+}
+
+impl<const CAPACITY: usize> FrameDecoder<CAPACITY> {
+    pub const fn new() -> Self {
+        Self {
+            state: DecoderState::Start,
+            received: 0,
+            header: [0; HEADER_FIELDS_SIZE],
+            length: 0,
+            payload: [0; CAPACITY],
+            checksum: [0; CHECKSUM_SIZE],
+            rescan: Rescan {
+                header: [0; HEADER_FIELDS_SIZE],
+                checksum: [0; CHECKSUM_SIZE],
+                payload_length: 0,
+                size: 0,
+                position: 0,
+            },
+        }
+    }
+
+    /// Drop partially received frame
+    pub fn reset(&mut self) {
+        self.advance(DecoderState::Start);
+        self.rescan.position = self.rescan.size;
+    }
+
+    /// Payload of the last decoded frame
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.length]
+    }
+
+    /// Sequence number of the last frame, even broken one
+    pub fn sequence(&self) -> u8 {
+        self.header[SEQUENCE_OFFSET]
+    }
+
+    /// Pulls bytes until frame is complete. Returns `None` once `next_byte` runs out.
+    /// Errors are reported for frames which are broken. Frames found among
+    /// bytes of the broken frame go before the bytes which are not pulled yet
+    pub fn next_frame(
+        &mut self,
+        mut next_byte: impl FnMut() -> Option<u8>,
+    ) -> Option<Result<FrameHeader, DecodeError>> {
+        loop {
+            if let Some(result) = self.rescan() {
+                return Some(result);
+            }
+
+            if let Some(result) = self.push(next_byte()?) {
+                return Some(result);
+            }
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> Option<Result<FrameHeader, DecodeError>> {
+        match self.state {
+            DecoderState::Start => {
+                if byte == FRAME_START[self.received] {
+                    self.received += 1;
+                } else {
+                    self.received = (byte == FRAME_START[0]) as usize;
+                }
+
+                if self.received == FRAME_START.len() {
+                    self.advance(DecoderState::Header);
+                }
+            }
+            DecoderState::Header => {
+                self.header[self.received] = byte;
+                self.received += 1;
+                if self.received < HEADER_FIELDS_SIZE {
+                    return None;
+                }
+
+                let length = u16::from_le_bytes([
+                    self.header[LENGTH_OFFSET],
+                    self.header[LENGTH_OFFSET + 1],
+                ]) as usize;
+                if length > CAPACITY {
+                    self.length = 0;
+                    self.start_rescan(HEADER_FIELDS_SIZE);
+                    return Some(Err(DecodeError::PayloadTooLong));
+                }
+
+                self.length = length;
+                match length {
+                    0 => self.advance(DecoderState::Checksum),
+                    _ => self.advance(DecoderState::Payload),
+                }
+            }
+            DecoderState::Payload => {
+                self.payload[self.received] = byte;
+                self.received += 1;
+                if self.received == self.length {
+                    self.advance(DecoderState::Checksum);
+                }
+            }
+            DecoderState::Checksum => {
+                self.checksum[self.received] = byte;
+                self.received += 1;
+                if self.received < CHECKSUM_SIZE {
+                    return None;
+                }
+
+                let result = self.complete();
+                match result {
+                    Err(DecodeError::ChecksumMismatch) => {
+                        self.start_rescan(HEADER_FIELDS_SIZE + self.length + CHECKSUM_SIZE)
+                    }
+                    _ => self.advance(DecoderState::Start),
+                }
+                return Some(result);
+            }
+        }
+
+        None
+    }
+
+    fn advance(&mut self, state: DecoderState) {
+        self.state = state;
+        self.received = 0;
+    }
+
+    /// Broken frame of `size` bytes after start marker is scanned again
+    /// before any new byte
+    fn start_rescan(&mut self, size: usize) {
+        self.rescan.header = self.header;
+        self.rescan.checksum = self.checksum;
+        self.rescan.payload_length = self.length;
+        self.rescan.size = size;
+        self.rescan.position = 0;
+        self.advance(DecoderState::Start);
+    }
+
+    fn rescanned(&self, index: usize) -> u8 {
+        let payload_end = HEADER_FIELDS_SIZE + self.rescan.payload_length;
+        if index < HEADER_FIELDS_SIZE {
+            self.rescan.header[index]
+        } else if index < payload_end {
+            self.payload[index - HEADER_FIELDS_SIZE]
+        } else {
+            self.rescan.checksum[index - payload_end]
+        }
+    }
+
+    /// The next frame found among bytes of the broken one. Frames are checked
+    /// in place and pushed through the decoder only when intact: pushing
+    /// overwrites payload up to the frame's end. Frame cut off by the end
+    /// of the broken one is pushed as far as it goes
+    fn rescan(&mut self) -> Option<Result<FrameHeader, DecodeError>> {
+        let size = self.rescan.size;
+        while self.rescan.position < size {
+            let start = self.rescan.position;
+            self.rescan.position += 1;
+            if self.rescanned(start) != FRAME_START[0] {
+                continue;
+            }
+
+            let header = start + FRAME_START.len();
+            let payload = header + HEADER_FIELDS_SIZE;
+            if payload > size {
+                return self.replay(start, size);
+            }
+
+            if self.rescanned(start + 1) != FRAME_START[1] {
+                continue;
+            }
+
+            for offset in 0..HEADER_FIELDS_SIZE {
+                self.header[offset] = self.rescanned(header + offset);
+            }
+            let length =
+                u16::from_le_bytes([self.header[LENGTH_OFFSET], self.header[LENGTH_OFFSET + 1]])
+                    as usize;
+            if length > CAPACITY {
+                return Some(Err(DecodeError::PayloadTooLong));
+            }
+
+            let checksum = payload + length;
+            let end = checksum + CHECKSUM_SIZE;
+            if end > size {
+                return self.replay(start, size);
+            }
+
+            let mut crc = Crc16::new();
+            for index in header..checksum {
+                crc.update(&[self.rescanned(index)]);
+            }
+            let expected =
+                u16::from_le_bytes([self.rescanned(checksum), self.rescanned(checksum + 1)]);
+            if crc.finish() != expected {
+                return Some(Err(DecodeError::ChecksumMismatch));
+            }
+
+            return self.replay(start, end);
+        }
+
+        None
+    }
+
+    fn replay(&mut self, start: usize, end: usize) -> Option<Result<FrameHeader, DecodeError>> {
+        self.rescan.position = end;
+        let mut result = None;
+        for index in start..end {
+            result = self.push(self.rescanned(index));
+        }
+        result
+    }
+
+    fn complete(&self) -> Result<FrameHeader, DecodeError> {
+        let mut crc = Crc16::new();
+        crc.update(&self.header);
+        crc.update(self.payload());
+        if crc.finish() != u16::from_le_bytes(self.checksum) {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+
+        Ok(FrameHeader {
+            message_type: MessageType::try_from(self.header[MESSAGE_TYPE_OFFSET])?,
+            sequence: self.sequence(),
+            length: self.length as u16,
+        })
+    }
+}
+
+impl<const CAPACITY: usize> Default for FrameDecoder<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    const CAPACITY: usize = 8;
+
+    fn frame(message_type: MessageType, sequence: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_frame(
+            |chunk| bytes.extend_from_slice(chunk),
+            message_type,
+            sequence,
+            payload,
+        );
+        bytes
+    }
+
+    /// Results of every completed or broken frame
+    fn decode(
+        decoder: &mut FrameDecoder<CAPACITY>,
+        bytes: &[u8],
+    ) -> Vec<Result<(FrameHeader, Vec<u8>), DecodeError>> {
+        let mut frames = Vec::new();
+        let mut bytes = bytes.iter().copied();
+        while let Some(result) = decoder.next_frame(|| bytes.next()) {
+            frames.push(result.map(|header| (header, decoder.payload().to_vec())));
+        }
+        frames
+    }
+
+    #[test]
+    fn checksum_matches_ccitt_false() {
+        let mut crc = Crc16::new();
+        crc.update(b"123456789");
+
+        assert_eq!(crc.finish(), 0x29b1);
+    }
+
+    #[test]
+    fn decodes_what_is_written() {
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &frame(MessageType::Response, 7, &[1, 2, 3]));
+
+        let [Ok((header, payload))] = &frames[..] else {
+            panic!("expected single frame, got {:?}", frames);
+        };
+        assert_eq!(header.message_type, MessageType::Response);
+        assert_eq!(header.sequence, 7);
+        assert_eq!(header.length, 3);
+        assert_eq!(payload, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn writer_in_pieces_matches_whole_frame() {
+        let mut bytes = Vec::new();
+        let mut writer = FrameWriter::new(
+            |chunk: &[u8]| bytes.extend_from_slice(chunk),
+            MessageType::Response,
+            1,
+            4,
+        );
+        writer.write(&[1, 2]);
+        writer.write(&[3, 4]);
+        writer.finish();
+
+        assert_eq!(bytes, frame(MessageType::Response, 1, &[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn empty_payload_is_decoded() {
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &frame(MessageType::Response, 1, &[]));
+
+        assert!(
+            matches!(&frames[..], [Ok((header, payload))] if header.length == 0 && payload.is_empty())
+        );
+    }
+
+    #[test]
+    fn noise_before_start_is_skipped() {
+        let mut bytes = std::vec![0x00, FRAME_START[0], 0x13, FRAME_START[0]];
+        bytes.extend(frame(MessageType::Request, 2, &[9]));
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &bytes);
+
+        assert!(
+            matches!(&frames[..], [Ok((header, payload))] if header.sequence == 2 && payload == &[9])
+        );
+    }
+
+    #[test]
+    fn corrupted_frame_is_dropped() {
+        let mut bytes = frame(MessageType::Response, 3, &[1, 2]);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        bytes.extend(frame(MessageType::Response, 4, &[5, 6]));
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &bytes);
+
+        assert!(matches!(
+            &frames[..],
+            [Err(DecodeError::ChecksumMismatch), Ok((header, payload))]
+                if header.sequence == 4 && payload == &[5, 6]
+        ));
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let bytes = frame(MessageType::Response, 5, &[1, 2, 3, 4, 5]);
+        let (first, second) = bytes.split_at(bytes.len() / 2);
+        let mut decoder = FrameDecoder::new();
+
+        assert!(decode(&mut decoder, first).is_empty());
+        let frames = decode(&mut decoder, second);
+
+        assert!(matches!(&frames[..], [Ok((_, payload))] if payload == &[1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn payload_over_capacity_is_rejected() {
+        let mut bytes = frame(MessageType::Response, 6, &[0; CAPACITY + 1]);
+        bytes.extend(frame(MessageType::Response, 7, &[1]));
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &bytes);
+
+        // the long payload is read as noise, so the next frame is found
+        assert!(matches!(
+            &frames[..],
+            [Err(DecodeError::PayloadTooLong), Ok((header, _))] if header.sequence == 7
+        ));
+    }
+
+    /// Length byte of the first frame is flipped
+    fn with_corrupted_length(payload: &[u8], length: u8) -> Vec<u8> {
+        let mut bytes = frame(MessageType::Response, 3, payload);
+        bytes[FRAME_START.len() + LENGTH_OFFSET] = length;
+        bytes
+    }
+
+    #[test]
+    fn corrupted_length_does_not_swallow_next_frame() {
+        let mut bytes = with_corrupted_length(&[1], CAPACITY as u8);
+        let next = frame(MessageType::Response, 4, &[5, 6]);
+        bytes.extend(&next);
+        let mut decoder = FrameDecoder::new();
+
+        // broken frame ends in the middle of the next one
+        let (first, second) = bytes.split_at(bytes.len() - 3);
+        let frames = decode(&mut decoder, first);
+        assert!(matches!(&frames[..], [Err(DecodeError::ChecksumMismatch)]));
+        let frames = decode(&mut decoder, second);
+
+        assert!(matches!(
+            &frames[..],
+            [Ok((header, payload))] if header.sequence == 4 && payload == &[5, 6]
+        ));
+    }
+
+    #[test]
+    fn frames_inside_broken_one_are_decoded() {
+        // broken frame takes the whole empty frame after it
+        let mut bytes = with_corrupted_length(&[], CAPACITY as u8);
+        bytes.extend(frame(MessageType::Response, 4, &[]));
+        bytes.extend(frame(MessageType::Response, 5, &[7]));
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &bytes);
+
+        assert!(matches!(
+            &frames[..],
+            [
+                Err(DecodeError::ChecksumMismatch),
+                Ok((inside, _)),
+                Ok((after, payload)),
+            ] if inside.sequence == 4 && after.sequence == 5 && payload == &[7]
+        ));
+    }
+
+    #[test]
+    fn header_of_too_long_frame_is_scanned_again() {
+        // start marker of the next frame is read as length
+        let mut bytes = std::vec![FRAME_START[0], FRAME_START[1], 0, 0];
+        bytes.extend(frame(MessageType::Response, 4, &[1]));
+        let mut decoder = FrameDecoder::new();
+
+        let frames = decode(&mut decoder, &bytes);
+
+        assert!(matches!(
+            &frames[..],
+            [Err(DecodeError::PayloadTooLong), Ok((header, _))] if header.sequence == 4
+        ));
+    }
+}
@@ -3,6 +3,12 @@
 use point::Point;
 
 pub mod byte_serializable;
+pub mod catalogue;
+pub mod decode_error;
+pub mod device_info;
+pub mod expression;
+pub mod frame;
+pub mod notification;
 pub mod point;
 pub mod request;
 pub mod response;
@@ -10,9 +16,13 @@ pub mod response;
 pub type TProtocolSignature = u64;
 pub const PROTOCOL_SIGNATURE_SIZE: usize = size_of::<TProtocolSignature>();
 pub const PROTOCOL_SIGNATURE: TProtocolSignature = 0x15_8d_c5_8c_30_4f_00_7b;
+/// Bumped whenever host and arduino stop understanding each other
+pub const PROTOCOL_VERSION: u8 = 5;
 pub const POINT_AMOUNT: usize = 256;
 pub const LONG_PACKAGE_SIZE: usize = Point::POINT_SIZE_BYTES * POINT_AMOUNT;
 pub const PACKAGE_SIZE: usize = 16;
+/// Firmware is built with this baud rate. Host uses it unless told otherwise
+pub const BAUD_RATE: u32 = 250_000;
 
 pub const T_NUMBER_SIZE_BYTES: usize = size_of::<TNumber>();
 pub type TNumber = f32;
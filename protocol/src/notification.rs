@@ -0,0 +1,46 @@
+use crate::byte_serializable::{read_field, ByteSerializable};
+use crate::decode_error::DecodeError;
+use crate::response::InitialApproximationsResponse;
+use crate::PACKAGE_SIZE;
+
+/// Event on arduino's side, which host did not ask about
+#[derive(Debug, Clone, Copy)]
+pub enum Notification {
+    /// Bracket was changed with buttons
+    InitialApproximationsChanged(InitialApproximationsResponse),
+}
+
+impl Notification {
+    pub const NOTIFICATION_SIZE: usize = 1 + PACKAGE_SIZE;
+
+    const INITIAL_APPROXIMATIONS_CHANGED: u8 = 0;
+
+    const KIND_OFFSET: usize = 0;
+    const PAYLOAD_OFFSET: usize = 1;
+}
+
+impl ByteSerializable<{ Self::NOTIFICATION_SIZE }> for Notification {
+    fn to_bytes(&self) -> [u8; Self::NOTIFICATION_SIZE] {
+        let mut bytes = [0; Self::NOTIFICATION_SIZE];
+        match self {
+            Notification::InitialApproximationsChanged(approximations) => {
+                bytes[Self::KIND_OFFSET] = Self::INITIAL_APPROXIMATIONS_CHANGED;
+                bytes[Self::PAYLOAD_OFFSET..].copy_from_slice(&approximations.to_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    fn try_from_bytes(raw_bytes: &[u8; Self::NOTIFICATION_SIZE]) -> Result<Self, DecodeError> {
+        match raw_bytes[Self::KIND_OFFSET] {
+            Self::INITIAL_APPROXIMATIONS_CHANGED => Ok(Self::InitialApproximationsChanged(
+                InitialApproximationsResponse::try_from_bytes(&read_field(
+                    raw_bytes,
+                    Self::PAYLOAD_OFFSET,
+                ))?,
+            )),
+            kind => Err(DecodeError::UnknownNotification(kind)),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use core::ops::{Add, Mul, Neg, Sub};
 
 use crate::byte_serializable::{read_field, ByteSerializable};
+use crate::decode_error::DecodeError;
 use crate::{TNumber, T_NUMBER_SIZE_BYTES};
 
 #[derive(Debug, Clone, Copy)]
@@ -100,11 +101,11 @@ impl ByteSerializable<{ Self::POINT_SIZE_BYTES }> for Point {
         bytes
     }
 
-    fn from_bytes(raw_bytes: &[u8; Self::POINT_SIZE_BYTES]) -> Self {
-        Self {
+    fn try_from_bytes(raw_bytes: &[u8; Self::POINT_SIZE_BYTES]) -> Result<Self, DecodeError> {
+        Ok(Self {
             x: TNumber::from_le_bytes(read_field(raw_bytes, 0)),
             y: TNumber::from_le_bytes(read_field(raw_bytes, T_NUMBER_SIZE_BYTES)),
-        }
+        })
     }
 }
 
@@ -1,4 +1,6 @@
 use crate::byte_serializable::{read_field, ByteSerializable};
+use crate::decode_error::DecodeError;
+use crate::expression::ProgramTarget;
 use crate::{TNumber, PACKAGE_SIZE, T_NUMBER_SIZE_BYTES};
 
 mod equation_mode;
@@ -7,7 +9,7 @@ pub mod payloads;
 
 pub use selection::Selection;
 pub use equation_mode::*;
-use payloads::ComputeRootPayload;
+use payloads::{ComputeRootPayload, StoppingCriterion, UploadChunk};
 
 #[derive(Copy, Clone, Debug)]
 pub enum RequestPackage {
@@ -21,15 +23,71 @@ pub enum RequestPackage {
     ComputeRoot {
         payload: ComputeRootPayload,
     },
+    /// Same as [`ComputeRoot`](RequestPackage::ComputeRoot), but state of
+    /// every iteration is sent back as [`TraceRecord`](crate::response::TraceRecord)
+    /// before the result
+    ComputeRootTrace {
+        payload: ComputeRootPayload,
+    },
+    /// Names of all equations. Await
+    /// [`EquationCatalogue`](crate::catalogue::EquationCatalogue)
+    ListEquations,
+    /// Replaces approximations set with buttons. Await
+    /// [`InitialApproximationsResponse`](crate::response::InitialApproximationsResponse)
+    /// with approximations in effect. They are left unchanged if requested
+    /// ones are out of range
+    SetInitialApproximations {
+        left: TNumber,
+        right: TNumber,
+    },
+    /// Roots of single equation within the whole plotted range. Range is split
+    /// by the points grid, intervals with sign change are refined with the selected method.
+    /// Await [`ComputeRootResponse`](crate::response::ComputeRootResponse) per interval,
+    /// then response with empty payload. Nothing is found for systems of equations
+    FindAllRoots {
+        payload: ComputeRootPayload,
+    },
+    /// Part of the [`Program`](crate::expression::Program) for the user equation slot.
+    /// Await response with empty payload per chunk. Program takes effect once
+    /// the last chunk is received. Broken program is answered with an error frame
+    UploadEquation {
+        payload: UploadChunk,
+    },
 }
 
 impl RequestPackage {
     const FUNCTION_POINTS_TYPE: u8 = 0;
     const INITIAL_APPROXIMATION_TYPE: u8 = 1;
     const COMPUTE_ROOT_TYPE: u8 = 2;
+    const COMPUTE_ROOT_TRACE_TYPE: u8 = 3;
+    const LIST_EQUATIONS_TYPE: u8 = 4;
+    const SET_INITIAL_APPROXIMATIONS_TYPE: u8 = 5;
+    const FIND_ALL_ROOTS_TYPE: u8 = 6;
+    const UPLOAD_EQUATION_TYPE: u8 = 7;
 
     const REQUEST_TYPE_OFFSET: usize = 0;
     const REQUEST_PAYLOAD_OFFSET: usize = 1;
+    const RIGHT_APPROXIMATION_OFFSET: usize = Self::REQUEST_PAYLOAD_OFFSET + T_NUMBER_SIZE_BYTES;
+
+    fn read_compute_root_payload(
+        raw_bytes: &[u8; PACKAGE_SIZE],
+    ) -> Result<ComputeRootPayload, DecodeError> {
+        let epsilon_bytes: [u8; T_NUMBER_SIZE_BYTES] =
+            read_field(raw_bytes, Self::REQUEST_PAYLOAD_OFFSET);
+        let mode_bytes: [u8; EquationMode::EQUATION_MODE_SIZE] =
+            read_field(raw_bytes, ComputeRootPayload::MODE_OFFSET);
+        let max_iterations_bytes: [u8; size_of::<u16>()] =
+            read_field(raw_bytes, ComputeRootPayload::MAX_ITERATIONS_OFFSET);
+
+        Ok(ComputeRootPayload {
+            epsilon: TNumber::from_le_bytes(epsilon_bytes),
+            mode: EquationMode::try_from_bytes(&mode_bytes)?,
+            max_iterations: u16::from_le_bytes(max_iterations_bytes),
+            criterion: StoppingCriterion::try_from(
+                raw_bytes[ComputeRootPayload::CRITERION_OFFSET],
+            )?,
+        })
+    }
 }
 
 impl ByteSerializable<PACKAGE_SIZE> for RequestPackage {
@@ -38,6 +96,13 @@ impl ByteSerializable<PACKAGE_SIZE> for RequestPackage {
             RequestPackage::FunctionPoints { .. } => RequestPackage::FUNCTION_POINTS_TYPE,
             RequestPackage::InitialApproximations => RequestPackage::INITIAL_APPROXIMATION_TYPE,
             RequestPackage::ComputeRoot { .. } => RequestPackage::COMPUTE_ROOT_TYPE,
+            RequestPackage::ComputeRootTrace { .. } => RequestPackage::COMPUTE_ROOT_TRACE_TYPE,
+            RequestPackage::ListEquations => RequestPackage::LIST_EQUATIONS_TYPE,
+            RequestPackage::SetInitialApproximations { .. } => {
+                RequestPackage::SET_INITIAL_APPROXIMATIONS_TYPE
+            }
+            RequestPackage::FindAllRoots { .. } => RequestPackage::FIND_ALL_ROOTS_TYPE,
+            RequestPackage::UploadEquation { .. } => RequestPackage::UPLOAD_EQUATION_TYPE,
         };
 
         let mut package: [u8; PACKAGE_SIZE] = [0; PACKAGE_SIZE];
@@ -48,8 +113,10 @@ impl ByteSerializable<PACKAGE_SIZE> for RequestPackage {
                     ..(RequestPackage::REQUEST_PAYLOAD_OFFSET
                         + Selection::FUNCTION_POINTS_PAYLOAD_SIZE)]
                 .copy_from_slice(&payload.to_bytes()),
-            RequestPackage::InitialApproximations => (),
-            RequestPackage::ComputeRoot { payload } => {
+            RequestPackage::InitialApproximations | RequestPackage::ListEquations => (),
+            RequestPackage::ComputeRoot { payload }
+            | RequestPackage::ComputeRootTrace { payload }
+            | RequestPackage::FindAllRoots { payload } => {
                 package[RequestPackage::REQUEST_PAYLOAD_OFFSET
                     ..(RequestPackage::REQUEST_PAYLOAD_OFFSET + T_NUMBER_SIZE_BYTES)]
                     .copy_from_slice(&payload.epsilon.to_le_bytes());
@@ -57,36 +124,74 @@ impl ByteSerializable<PACKAGE_SIZE> for RequestPackage {
                 package[ComputeRootPayload::MODE_OFFSET
                     ..(ComputeRootPayload::MODE_OFFSET + EquationMode::EQUATION_MODE_SIZE)]
                     .copy_from_slice(&payload.mode.to_bytes());
+
+                package[ComputeRootPayload::MAX_ITERATIONS_OFFSET
+                    ..ComputeRootPayload::CRITERION_OFFSET]
+                    .copy_from_slice(&payload.max_iterations.to_le_bytes());
+                package[ComputeRootPayload::CRITERION_OFFSET] = payload.criterion.to_byte();
+            }
+            RequestPackage::SetInitialApproximations { left, right } => {
+                package[RequestPackage::REQUEST_PAYLOAD_OFFSET
+                    ..RequestPackage::RIGHT_APPROXIMATION_OFFSET]
+                    .copy_from_slice(&left.to_le_bytes());
+                package[RequestPackage::RIGHT_APPROXIMATION_OFFSET
+                    ..(RequestPackage::RIGHT_APPROXIMATION_OFFSET + T_NUMBER_SIZE_BYTES)]
+                    .copy_from_slice(&right.to_le_bytes());
+            }
+            RequestPackage::UploadEquation { payload } => {
+                package[UploadChunk::TARGET_OFFSET] = payload.target.to_byte();
+                package[UploadChunk::START_OFFSET] = payload.start;
+                package[UploadChunk::LENGTH_OFFSET] = payload.length;
+                package[UploadChunk::BYTES_OFFSET..].copy_from_slice(&payload.bytes);
             }
         };
 
         package
     }
 
-    fn from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> RequestPackage {
+    fn try_from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> Result<RequestPackage, DecodeError> {
         let request_type = raw_bytes[RequestPackage::REQUEST_TYPE_OFFSET];
-        match request_type {
+        let request = match request_type {
             RequestPackage::FUNCTION_POINTS_TYPE => RequestPackage::FunctionPoints {
-                payload: Selection::from_bytes(&read_field(
+                payload: Selection::try_from_bytes(&read_field(
                     raw_bytes,
                     Self::REQUEST_PAYLOAD_OFFSET,
-                )),
+                ))?,
             },
             RequestPackage::INITIAL_APPROXIMATION_TYPE => RequestPackage::InitialApproximations,
-            RequestPackage::COMPUTE_ROOT_TYPE => {
-                let epsilon_bytes: [u8; T_NUMBER_SIZE_BYTES] =
-                    read_field(raw_bytes, Self::REQUEST_PAYLOAD_OFFSET);
-                let mode_bytes: [u8; EquationMode::EQUATION_MODE_SIZE] =
-                    read_field(raw_bytes, ComputeRootPayload::MODE_OFFSET);
-
-                RequestPackage::ComputeRoot {
-                    payload: ComputeRootPayload {
-                        epsilon: TNumber::from_le_bytes(epsilon_bytes),
-                        mode: EquationMode::from_bytes(&mode_bytes),
-                    },
+            RequestPackage::COMPUTE_ROOT_TYPE => RequestPackage::ComputeRoot {
+                payload: Self::read_compute_root_payload(raw_bytes)?,
+            },
+            RequestPackage::COMPUTE_ROOT_TRACE_TYPE => RequestPackage::ComputeRootTrace {
+                payload: Self::read_compute_root_payload(raw_bytes)?,
+            },
+            RequestPackage::LIST_EQUATIONS_TYPE => RequestPackage::ListEquations,
+            RequestPackage::FIND_ALL_ROOTS_TYPE => RequestPackage::FindAllRoots {
+                payload: Self::read_compute_root_payload(raw_bytes)?,
+            },
+            RequestPackage::SET_INITIAL_APPROXIMATIONS_TYPE => {
+                RequestPackage::SetInitialApproximations {
+                    left: TNumber::from_le_bytes(read_field(
+                        raw_bytes,
+                        Self::REQUEST_PAYLOAD_OFFSET,
+                    )),
+                    right: TNumber::from_le_bytes(read_field(
+                        raw_bytes,
+                        Self::RIGHT_APPROXIMATION_OFFSET,
+                    )),
                 }
             }
-            _ => unreachable!(),
-        }
+            RequestPackage::UPLOAD_EQUATION_TYPE => RequestPackage::UploadEquation {
+                payload: UploadChunk {
+                    target: ProgramTarget::try_from(raw_bytes[UploadChunk::TARGET_OFFSET])?,
+                    start: raw_bytes[UploadChunk::START_OFFSET],
+                    length: raw_bytes[UploadChunk::LENGTH_OFFSET],
+                    bytes: read_field(raw_bytes, UploadChunk::BYTES_OFFSET),
+                },
+            },
+            _ => return Err(DecodeError::UnknownRequestType(request_type)),
+        };
+
+        Ok(request)
     }
 }
@@ -1,6 +1,7 @@
-use compute_method::Method;
+use compute_method::{Method, SystemMethod};
 
 use crate::byte_serializable::ByteSerializable;
+use crate::decode_error::DecodeError;
 
 pub mod compute_method;
 
@@ -13,7 +14,10 @@ pub struct SingleEquation {
 #[derive(Copy, Clone, Debug)]
 pub enum EquationMode {
     Single(SingleEquation),
-    SystemOfEquations { system_number: u8 },
+    SystemOfEquations {
+        method: SystemMethod,
+        system_number: u8,
+    },
 }
 
 impl EquationMode {
@@ -21,7 +25,7 @@ impl EquationMode {
 
     const METHOD_OFFSET: usize = 1;
     const EQUATION_NUMBER_OFFSET: usize = 2;
-    const SYSTEM_NUMBER_OFFSET: usize = 1;
+    const SYSTEM_NUMBER_OFFSET: usize = 2;
 }
 
 impl ByteSerializable<{ Self::EQUATION_MODE_SIZE }> for EquationMode {
@@ -36,8 +40,12 @@ impl ByteSerializable<{ Self::EQUATION_MODE_SIZE }> for EquationMode {
                 bytes[Self::METHOD_OFFSET] = method.to_byte();
                 bytes[Self::EQUATION_NUMBER_OFFSET] = *equation_number;
             }
-            &EquationMode::SystemOfEquations { system_number } => {
+            &EquationMode::SystemOfEquations {
+                method,
+                system_number,
+            } => {
                 bytes[0] = EquationModeRaw::SYSTEM_OF_EQUATIONS_MODE;
+                bytes[Self::METHOD_OFFSET] = method.to_byte();
                 bytes[Self::SYSTEM_NUMBER_OFFSET] = system_number;
             }
         }
@@ -45,16 +53,16 @@ impl ByteSerializable<{ Self::EQUATION_MODE_SIZE }> for EquationMode {
         bytes
     }
 
-    fn from_bytes(raw_bytes: &[u8; Self::EQUATION_MODE_SIZE]) -> Self {
-        match raw_bytes[0] {
-            EquationModeRaw::SINGLE_EQUATION_MODE => Self::Single(SingleEquation {
-                method: Method::from_byte(raw_bytes[Self::METHOD_OFFSET]),
+    fn try_from_bytes(raw_bytes: &[u8; Self::EQUATION_MODE_SIZE]) -> Result<Self, DecodeError> {
+        match EquationModeRaw::try_from(raw_bytes[0])? {
+            EquationModeRaw::SingleEquation => Ok(Self::Single(SingleEquation {
+                method: Method::try_from(raw_bytes[Self::METHOD_OFFSET])?,
                 equation_number: raw_bytes[Self::EQUATION_NUMBER_OFFSET],
-            }),
-            EquationModeRaw::SYSTEM_OF_EQUATIONS_MODE => Self::SystemOfEquations {
+            })),
+            EquationModeRaw::SystemOfEquations => Ok(Self::SystemOfEquations {
+                method: SystemMethod::try_from(raw_bytes[Self::METHOD_OFFSET])?,
                 system_number: raw_bytes[Self::SYSTEM_NUMBER_OFFSET],
-            },
-            _ => unreachable!(),
+            }),
         }
     }
 }
@@ -70,6 +78,18 @@ impl EquationModeRaw {
     pub(super) const SYSTEM_OF_EQUATIONS_MODE: u8 = 1;
 }
 
+impl TryFrom<u8> for EquationModeRaw {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            EquationModeRaw::SINGLE_EQUATION_MODE => Ok(EquationModeRaw::SingleEquation),
+            EquationModeRaw::SYSTEM_OF_EQUATIONS_MODE => Ok(EquationModeRaw::SystemOfEquations),
+            _ => Err(DecodeError::UnknownEquationMode(byte)),
+        }
+    }
+}
+
 impl From<&EquationMode> for EquationModeRaw {
     fn from(value: &EquationMode) -> Self {
         match value {
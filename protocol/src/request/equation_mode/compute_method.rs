@@ -1,31 +1,50 @@
 use core::fmt::Display;
 
+use crate::decode_error::DecodeError;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Method {
     Chord,
     Secant,
     SimpleIterationSingle,
+    Newton,
+    Bisection,
 }
 
 impl Method {
     const CHORD: u8 = 0;
     const SECANT: u8 = 1;
     const SIMPLE_ITERATION_SINGLE: u8 = 2;
+    const NEWTON: u8 = 3;
+    const BISECTION: u8 = 4;
 
     pub fn to_byte(&self) -> u8 {
         match self {
             Method::Chord => Method::CHORD,
             Method::Secant => Method::SECANT,
             Method::SimpleIterationSingle => Method::SIMPLE_ITERATION_SINGLE,
+            Method::Newton => Method::NEWTON,
+            Method::Bisection => Method::BISECTION,
         }
     }
 
-    pub fn from_byte(byte: u8) -> Method {
+    /// Bit of the method in [`DeviceInfo::methods`](crate::device_info::DeviceInfo::methods)
+    pub fn mask(&self) -> u8 {
+        1 << self.to_byte()
+    }
+}
+
+impl TryFrom<u8> for Method {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
         match byte {
-            Method::CHORD => Method::Chord,
-            Method::SECANT => Method::Secant,
-            Method::SIMPLE_ITERATION_SINGLE => Method::SimpleIterationSingle,
-            _ => unreachable!(),
+            Method::CHORD => Ok(Method::Chord),
+            Method::SECANT => Ok(Method::Secant),
+            Method::SIMPLE_ITERATION_SINGLE => Ok(Method::SimpleIterationSingle),
+            Method::NEWTON => Ok(Method::Newton),
+            Method::BISECTION => Ok(Method::Bisection),
+            _ => Err(DecodeError::UnknownMethod(byte)),
         }
     }
 }
@@ -36,6 +55,56 @@ impl Display for Method {
             Method::Chord => "Chord",
             Method::Secant => "Secant",
             Method::SimpleIterationSingle => "Simple Iteration",
+            Method::Newton => "Newton",
+            Method::Bisection => "Bisection",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// Methods applicable to system of non-linear equations
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SystemMethod {
+    SimpleIteration,
+    Newton,
+}
+
+impl SystemMethod {
+    const SIMPLE_ITERATION: u8 = 0;
+    const NEWTON: u8 = 1;
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            SystemMethod::SimpleIteration => SystemMethod::SIMPLE_ITERATION,
+            SystemMethod::Newton => SystemMethod::NEWTON,
+        }
+    }
+
+    /// Bit of the method in
+    /// [`DeviceInfo::system_methods`](crate::device_info::DeviceInfo::system_methods)
+    pub fn mask(&self) -> u8 {
+        1 << self.to_byte()
+    }
+}
+
+impl TryFrom<u8> for SystemMethod {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            SystemMethod::SIMPLE_ITERATION => Ok(SystemMethod::SimpleIteration),
+            SystemMethod::NEWTON => Ok(SystemMethod::Newton),
+            _ => Err(DecodeError::UnknownSystemMethod(byte)),
+        }
+    }
+}
+
+impl Display for SystemMethod {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let string = match self {
+            SystemMethod::SimpleIteration => "Simple Iteration",
+            SystemMethod::Newton => "Newton",
         };
 
         write!(f, "{}", string)
@@ -1,4 +1,8 @@
-use crate::{TNumber, T_NUMBER_SIZE_BYTES};
+use core::fmt::Display;
+
+use crate::decode_error::DecodeError;
+use crate::expression::{Program, ProgramTarget};
+use crate::{TNumber, PACKAGE_SIZE, T_NUMBER_SIZE_BYTES};
 
 use super::{EquationMode, RequestPackage};
 
@@ -6,8 +10,107 @@ use super::{EquationMode, RequestPackage};
 pub struct ComputeRootPayload {
     pub epsilon: TNumber,
     pub mode: EquationMode,
+    /// Method fails once this amount of iterations is exceeded
+    pub max_iterations: u16,
+    pub criterion: StoppingCriterion,
 }
 
 impl ComputeRootPayload {
     pub const MODE_OFFSET: usize = RequestPackage::REQUEST_PAYLOAD_OFFSET + T_NUMBER_SIZE_BYTES;
+    pub const MAX_ITERATIONS_OFFSET: usize = Self::MODE_OFFSET + EquationMode::EQUATION_MODE_SIZE;
+    pub const CRITERION_OFFSET: usize = Self::MAX_ITERATIONS_OFFSET + size_of::<u16>();
+}
+
+/// Piece of the [`Program`] upload. Chunks are sent in order,
+/// program is complete once chunk which reaches `length` arrives
+#[derive(Copy, Clone, Debug)]
+pub struct UploadChunk {
+    pub target: ProgramTarget,
+    /// Index of the first byte within the program
+    pub start: u8,
+    /// Length of the whole program
+    pub length: u8,
+    /// Bytes past the end of the program are zeros
+    pub bytes: [u8; UploadChunk::CHUNK_SIZE],
+}
+
+impl UploadChunk {
+    pub const TARGET_OFFSET: usize = RequestPackage::REQUEST_PAYLOAD_OFFSET;
+    pub const START_OFFSET: usize = Self::TARGET_OFFSET + size_of::<u8>();
+    pub const LENGTH_OFFSET: usize = Self::START_OFFSET + size_of::<u8>();
+    pub const BYTES_OFFSET: usize = Self::LENGTH_OFFSET + size_of::<u8>();
+    /// Program bytes per request
+    pub const CHUNK_SIZE: usize = PACKAGE_SIZE - Self::BYTES_OFFSET;
+
+    /// Chunks in the order of upload
+    pub fn split(
+        target: ProgramTarget,
+        program: &Program,
+    ) -> impl Iterator<Item = UploadChunk> + '_ {
+        let program = program.as_bytes();
+        program
+            .chunks(Self::CHUNK_SIZE)
+            .enumerate()
+            .map(move |(index, piece)| {
+                let mut bytes = [0; Self::CHUNK_SIZE];
+                bytes[..piece.len()].copy_from_slice(piece);
+                UploadChunk {
+                    target,
+                    start: (index * Self::CHUNK_SIZE) as u8,
+                    length: program.len() as u8,
+                    bytes,
+                }
+            })
+    }
+}
+
+/// When method considers root to be precise enough
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StoppingCriterion {
+    /// *|x_k - x_{k-1}| <= epsilon*. Methods which estimate error
+    /// in a different way compare their estimate instead
+    Step,
+    /// *|f(x_k)| <= epsilon*
+    Residual,
+    /// Both step and residual are within epsilon
+    Both,
+}
+
+impl StoppingCriterion {
+    const STEP: u8 = 0;
+    const RESIDUAL: u8 = 1;
+    const BOTH: u8 = 2;
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            StoppingCriterion::Step => StoppingCriterion::STEP,
+            StoppingCriterion::Residual => StoppingCriterion::RESIDUAL,
+            StoppingCriterion::Both => StoppingCriterion::BOTH,
+        }
+    }
+}
+
+impl TryFrom<u8> for StoppingCriterion {
+    type Error = DecodeError;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            StoppingCriterion::STEP => Ok(StoppingCriterion::Step),
+            StoppingCriterion::RESIDUAL => Ok(StoppingCriterion::Residual),
+            StoppingCriterion::BOTH => Ok(StoppingCriterion::Both),
+            _ => Err(DecodeError::UnknownStoppingCriterion(byte)),
+        }
+    }
+}
+
+impl Display for StoppingCriterion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let string = match self {
+            StoppingCriterion::Step => "Step",
+            StoppingCriterion::Residual => "Residual",
+            StoppingCriterion::Both => "Step and residual",
+        };
+
+        write!(f, "{}", string)
+    }
 }
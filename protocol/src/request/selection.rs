@@ -1,4 +1,5 @@
 use crate::byte_serializable::ByteSerializable;
+use crate::decode_error::DecodeError;
 
 use super::{EquationMode, EquationModeRaw};
 
@@ -22,20 +23,16 @@ impl ByteSerializable<{ Self::FUNCTION_POINTS_PAYLOAD_SIZE }> for Selection {
         [mode_byte, self.index]
     }
 
-    fn from_bytes(raw_bytes: &[u8; Self::FUNCTION_POINTS_PAYLOAD_SIZE]) -> Self {
+    fn try_from_bytes(
+        raw_bytes: &[u8; Self::FUNCTION_POINTS_PAYLOAD_SIZE],
+    ) -> Result<Self, DecodeError> {
         let mode_byte = raw_bytes[0];
         let equation_number = raw_bytes[1];
 
-        let mode = match mode_byte {
-            EquationModeRaw::SINGLE_EQUATION_MODE => EquationModeRaw::SingleEquation,
-            EquationModeRaw::SYSTEM_OF_EQUATIONS_MODE => EquationModeRaw::SystemOfEquations,
-            _ => unreachable!(),
-        };
-
-        Self {
-            mode,
+        Ok(Self {
+            mode: EquationModeRaw::try_from(mode_byte)?,
             index: equation_number,
-        }
+        })
     }
 }
 
@@ -46,7 +43,7 @@ impl From<EquationMode> for Selection {
                 mode: EquationModeRaw::SingleEquation,
                 index: single.equation_number,
             },
-            EquationMode::SystemOfEquations { system_number } => Selection {
+            EquationMode::SystemOfEquations { system_number, .. } => Selection {
                 mode: EquationModeRaw::SystemOfEquations,
                 index: system_number,
             },
@@ -2,29 +2,67 @@ use core::fmt::Display;
 
 use crate::{
     byte_serializable::{read_field, ByteSerializable},
+    decode_error::DecodeError,
     point::Point,
     TNumber, LONG_PACKAGE_SIZE, PACKAGE_SIZE, POINT_AMOUNT, T_NUMBER_SIZE_BYTES,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MethodError {
-    NoRootInRange,
-    MoreThanOneRootInRange,
+    /// `suggestion` is the nearest bracket which isolates a single root, if any
+    NoRootInRange {
+        suggestion: Option<InitialApproximationsResponse>,
+    },
+    MoreThanOneRootInRange {
+        suggestion: Option<InitialApproximationsResponse>,
+    },
     Diverges,
+    /// Sufficient convergence condition does not hold
+    /// for initial approximations
+    ConvergenceConditionNotMet,
+    /// NaN or infinity encountered. Function is evaluated out of its domain
+    /// or iterations overflow
+    NotFinite,
+    /// Denominator of the iteration formula is zero. E.g. chord or secant
+    /// with equal function values at both ends
+    ZeroDenominator,
+    /// Precision is not reached within `max_iterations`
+    IterationLimitReached,
 }
 
 impl MethodError {
     const NO_ROOT_IN_RANGE: u8 = 0;
     const MORE_THAN_ONE_ROOT_IN_RANGE: u8 = 1;
     const DIVERGES: u8 = 2;
+    const CONVERGENCE_CONDITION_NOT_MET: u8 = 3;
+    const NOT_FINITE: u8 = 4;
+    const ZERO_DENOMINATOR: u8 = 5;
+    const ITERATION_LIMIT_REACHED: u8 = 6;
+
+    /// Bracket to move initial approximations to
+    pub fn suggestion(&self) -> Option<InitialApproximationsResponse> {
+        match self {
+            MethodError::NoRootInRange { suggestion }
+            | MethodError::MoreThanOneRootInRange { suggestion } => *suggestion,
+            _ => None,
+        }
+    }
 }
 
 impl Display for MethodError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            MethodError::NoRootInRange => write!(f, "No roots found withing range"),
-            MethodError::MoreThanOneRootInRange => write!(f, "More than one root withing range"),
+            MethodError::NoRootInRange { .. } => write!(f, "No roots found withing range"),
+            MethodError::MoreThanOneRootInRange { .. } => {
+                write!(f, "More than one root withing range")
+            }
             MethodError::Diverges => write!(f, "Method diverges"),
+            MethodError::ConvergenceConditionNotMet => {
+                write!(f, "Convergence condition is not met")
+            }
+            MethodError::NotFinite => write!(f, "NaN or infinity encountered"),
+            MethodError::ZeroDenominator => write!(f, "Division by zero"),
+            MethodError::IterationLimitReached => write!(f, "Iteration limit reached"),
         }
     }
 }
@@ -35,48 +73,92 @@ pub enum ResponsePackage {
     ComputeRoot(Result<ComputeRootResponse, MethodError>),
     FunctionPoints(FunctionPointsResponse),
     FunctionPointsSecond(FunctionPointsResponse),
+    ComputeRootTrace(TraceRecord),
 }
 
-impl ByteSerializable<PACKAGE_SIZE> for Result<ComputeRootResponse, MethodError> {
-    fn to_bytes(&self) -> [u8; PACKAGE_SIZE] {
+impl ByteSerializable<{ ComputeRootResponse::COMPUTE_ROOT_RESPONSE_SIZE }>
+    for Result<ComputeRootResponse, MethodError>
+{
+    fn to_bytes(&self) -> [u8; ComputeRootResponse::COMPUTE_ROOT_RESPONSE_SIZE] {
         match self {
             Ok(response) => response.to_bytes(),
             Err(error) => {
                 const ROOT_OFFSET: usize = ComputeRootResponse::ROOT_OFFSET;
-                let mut bytes = [0; PACKAGE_SIZE];
+                let mut bytes = [0; ComputeRootResponse::COMPUTE_ROOT_RESPONSE_SIZE];
 
                 bytes[ROOT_OFFSET..ROOT_OFFSET + T_NUMBER_SIZE_BYTES]
                     .copy_from_slice(&TNumber::to_le_bytes(TNumber::NAN));
 
                 let error_status = match error {
-                    MethodError::NoRootInRange => MethodError::NO_ROOT_IN_RANGE,
-                    MethodError::MoreThanOneRootInRange => MethodError::MORE_THAN_ONE_ROOT_IN_RANGE,
+                    MethodError::NoRootInRange { .. } => MethodError::NO_ROOT_IN_RANGE,
+                    MethodError::MoreThanOneRootInRange { .. } => {
+                        MethodError::MORE_THAN_ONE_ROOT_IN_RANGE
+                    }
                     MethodError::Diverges => MethodError::DIVERGES,
+                    MethodError::ConvergenceConditionNotMet => {
+                        MethodError::CONVERGENCE_CONDITION_NOT_MET
+                    }
+                    MethodError::NotFinite => MethodError::NOT_FINITE,
+                    MethodError::ZeroDenominator => MethodError::ZERO_DENOMINATOR,
+                    MethodError::IterationLimitReached => MethodError::ITERATION_LIMIT_REACHED,
                 };
 
                 bytes[ComputeRootResponse::STATUS_OFFSET] = error_status;
 
+                // `NaN` left border marks absent suggestion
+                let suggestion = error.suggestion().unwrap_or(InitialApproximationsResponse {
+                    left: TNumber::NAN,
+                    right: TNumber::NAN,
+                });
+                const LEFT_OFFSET: usize = ComputeRootResponse::SUGGESTION_LEFT_OFFSET;
+                const RIGHT_OFFSET: usize = ComputeRootResponse::SUGGESTION_RIGHT_OFFSET;
+                bytes[LEFT_OFFSET..RIGHT_OFFSET].copy_from_slice(&suggestion.left.to_le_bytes());
+                bytes[RIGHT_OFFSET..RIGHT_OFFSET + T_NUMBER_SIZE_BYTES]
+                    .copy_from_slice(&suggestion.right.to_le_bytes());
+
                 bytes
             }
         }
     }
 
-    fn from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> Self {
+    fn try_from_bytes(
+        raw_bytes: &[u8; ComputeRootResponse::COMPUTE_ROOT_RESPONSE_SIZE],
+    ) -> Result<Self, DecodeError> {
         let marker_bytes: [u8; T_NUMBER_SIZE_BYTES] =
             read_field(raw_bytes, ComputeRootResponse::ROOT_OFFSET);
         let marker = TNumber::from_le_bytes(marker_bytes);
         if !marker.is_nan() {
-            return Ok(ComputeRootResponse::from_bytes(raw_bytes));
+            return Ok(Ok(ComputeRootResponse::try_from_bytes(raw_bytes)?));
         }
 
-        let error = match raw_bytes[ComputeRootResponse::STATUS_OFFSET] {
-            MethodError::NO_ROOT_IN_RANGE => MethodError::NoRootInRange,
-            MethodError::MORE_THAN_ONE_ROOT_IN_RANGE => MethodError::MoreThanOneRootInRange,
+        let left = TNumber::from_le_bytes(read_field(
+            raw_bytes,
+            ComputeRootResponse::SUGGESTION_LEFT_OFFSET,
+        ));
+        let right = TNumber::from_le_bytes(read_field(
+            raw_bytes,
+            ComputeRootResponse::SUGGESTION_RIGHT_OFFSET,
+        ));
+        let suggestion = match left.is_nan() {
+            true => None,
+            false => Some(InitialApproximationsResponse { left, right }),
+        };
+
+        let status = raw_bytes[ComputeRootResponse::STATUS_OFFSET];
+        let error = match status {
+            MethodError::NO_ROOT_IN_RANGE => MethodError::NoRootInRange { suggestion },
+            MethodError::MORE_THAN_ONE_ROOT_IN_RANGE => {
+                MethodError::MoreThanOneRootInRange { suggestion }
+            }
             MethodError::DIVERGES => MethodError::Diverges,
-            _ => unreachable!(),
+            MethodError::CONVERGENCE_CONDITION_NOT_MET => MethodError::ConvergenceConditionNotMet,
+            MethodError::NOT_FINITE => MethodError::NotFinite,
+            MethodError::ZERO_DENOMINATOR => MethodError::ZeroDenominator,
+            MethodError::ITERATION_LIMIT_REACHED => MethodError::IterationLimitReached,
+            _ => return Err(DecodeError::UnknownMethodError(status)),
         };
 
-        Err(error)
+        Ok(Err(error))
     }
 }
 
@@ -92,6 +174,12 @@ impl From<Result<ComputeRootResponse, MethodError>> for ResponsePackage {
     }
 }
 
+impl From<TraceRecord> for ResponsePackage {
+    fn from(value: TraceRecord) -> Self {
+        Self::ComputeRootTrace(value)
+    }
+}
+
 impl From<FunctionPointsResponse> for ResponsePackage {
     fn from(value: FunctionPointsResponse) -> Self {
         Self::FunctionPoints(value)
@@ -124,7 +212,7 @@ impl ByteSerializable<PACKAGE_SIZE> for InitialApproximationsResponse {
         package
     }
 
-    fn from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> Self {
+    fn try_from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> Result<Self, DecodeError> {
         let left_bytes: [u8; T_NUMBER_SIZE_BYTES] = read_field(raw_bytes, Self::LEFT_BYTES_OFFSET);
         let left = TNumber::from_le_bytes(left_bytes);
 
@@ -134,34 +222,58 @@ impl ByteSerializable<PACKAGE_SIZE> for InitialApproximationsResponse {
             .unwrap();
         let right = TNumber::from_le_bytes(right_bytes);
 
-        Self { left, right }
+        Ok(Self { left, right })
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct ComputeRootResponse {
     pub root: Point,
+    /// Iterations made before precision was reached
+    pub iterations: u16,
+    /// *|x_k - x_{k-1}|* of the last iteration
+    pub step: TNumber,
+    /// *|f(x_k)|*. Largest of both residuals for system of equations
+    pub residual: TNumber,
 }
 
 impl ComputeRootResponse {
+    /// Longer than [`PACKAGE_SIZE`]: response travels in a frame of its own length
+    pub const COMPUTE_ROOT_RESPONSE_SIZE: usize =
+        Point::POINT_SIZE_BYTES + size_of::<u16>() + 2 * T_NUMBER_SIZE_BYTES;
+
     const ROOT_OFFSET: usize = ResponsePackage::PAYLOAD_OFFSET;
     const STATUS_OFFSET: usize = ResponsePackage::PAYLOAD_OFFSET + T_NUMBER_SIZE_BYTES;
+    /// Suggested bracket follows the status of an error
+    const SUGGESTION_LEFT_OFFSET: usize = Self::STATUS_OFFSET + size_of::<u8>();
+    const SUGGESTION_RIGHT_OFFSET: usize = Self::SUGGESTION_LEFT_OFFSET + T_NUMBER_SIZE_BYTES;
+    const ITERATIONS_OFFSET: usize = Self::ROOT_OFFSET + Point::POINT_SIZE_BYTES;
+    const STEP_OFFSET: usize = Self::ITERATIONS_OFFSET + size_of::<u16>();
+    const RESIDUAL_OFFSET: usize = Self::STEP_OFFSET + T_NUMBER_SIZE_BYTES;
 }
 
-impl ByteSerializable<PACKAGE_SIZE> for ComputeRootResponse {
-    fn to_bytes(&self) -> [u8; PACKAGE_SIZE] {
-        let mut package: [u8; PACKAGE_SIZE] = [0; PACKAGE_SIZE];
-        package[Self::ROOT_OFFSET..(Self::ROOT_OFFSET + Point::POINT_SIZE_BYTES)]
-            .copy_from_slice(&self.root.to_bytes());
+impl ByteSerializable<{ Self::COMPUTE_ROOT_RESPONSE_SIZE }> for ComputeRootResponse {
+    fn to_bytes(&self) -> [u8; Self::COMPUTE_ROOT_RESPONSE_SIZE] {
+        let mut package = [0; Self::COMPUTE_ROOT_RESPONSE_SIZE];
+        package[Self::ROOT_OFFSET..Self::ITERATIONS_OFFSET].copy_from_slice(&self.root.to_bytes());
+        package[Self::ITERATIONS_OFFSET..Self::STEP_OFFSET]
+            .copy_from_slice(&self.iterations.to_le_bytes());
+        package[Self::STEP_OFFSET..Self::RESIDUAL_OFFSET].copy_from_slice(&self.step.to_le_bytes());
+        package[Self::RESIDUAL_OFFSET..].copy_from_slice(&self.residual.to_le_bytes());
 
         package
     }
 
-    fn from_bytes(raw_bytes: &[u8; PACKAGE_SIZE]) -> Self {
+    fn try_from_bytes(
+        raw_bytes: &[u8; Self::COMPUTE_ROOT_RESPONSE_SIZE],
+    ) -> Result<Self, DecodeError> {
         let point_bytes: [u8; Point::POINT_SIZE_BYTES] = read_field(raw_bytes, Self::ROOT_OFFSET);
-        ComputeRootResponse {
-            root: Point::from_bytes(&point_bytes),
-        }
+        Ok(ComputeRootResponse {
+            root: Point::try_from_bytes(&point_bytes)?,
+            iterations: u16::from_le_bytes(read_field(raw_bytes, Self::ITERATIONS_OFFSET)),
+            step: TNumber::from_le_bytes(read_field(raw_bytes, Self::STEP_OFFSET)),
+            residual: TNumber::from_le_bytes(read_field(raw_bytes, Self::RESIDUAL_OFFSET)),
+        })
     }
 }
 
@@ -170,13 +282,81 @@ impl ByteSerializable<PACKAGE_SIZE> for ComputeRootResponse {
 #[derive(Debug, Clone, Copy)]
 pub struct FunctionPointsResponse(pub [Point; POINT_AMOUNT]);
 
-impl From<&[u8; LONG_PACKAGE_SIZE]> for FunctionPointsResponse {
-    fn from(value: &[u8; LONG_PACKAGE_SIZE]) -> Self {
+impl TryFrom<&[u8; LONG_PACKAGE_SIZE]> for FunctionPointsResponse {
+    type Error = DecodeError;
+
+    fn try_from(value: &[u8; LONG_PACKAGE_SIZE]) -> Result<Self, Self::Error> {
         let mut points: [Point; POINT_AMOUNT] = [Point::zero(); POINT_AMOUNT];
         for (index, point) in points.iter_mut().enumerate() {
-            *point = Point::from_bytes(&read_field(value, index * Point::POINT_SIZE_BYTES))
+            *point = Point::try_from_bytes(&read_field(value, index * Point::POINT_SIZE_BYTES))?
         }
 
-        FunctionPointsResponse(points)
+        Ok(FunctionPointsResponse(points))
+    }
+}
+
+/// State of the method after a single iteration.
+///
+/// Records are sent one after another while root is being computed.
+/// Stream of records ends with [terminator](TraceRecord::terminator).
+/// Then [`ComputeRootResponse`] follows.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceRecord {
+    /// *(x_k, f(x_k))* for single equation. *(x_k, y_k)* for system of equations
+    pub point: Point,
+    /// *|x_k - x_{k-1}|*
+    pub step: TNumber,
+    /// Left border of the interval. `NaN` for non-bracketing methods
+    pub left: TNumber,
+    /// Right border of the interval. `NaN` for non-bracketing methods
+    pub right: TNumber,
+}
+
+impl TraceRecord {
+    pub const TRACE_RECORD_SIZE: usize = Point::POINT_SIZE_BYTES + 3 * T_NUMBER_SIZE_BYTES;
+
+    const POINT_OFFSET: usize = 0;
+    const STEP_OFFSET: usize = Self::POINT_OFFSET + Point::POINT_SIZE_BYTES;
+    const LEFT_OFFSET: usize = Self::STEP_OFFSET + T_NUMBER_SIZE_BYTES;
+    const RIGHT_OFFSET: usize = Self::LEFT_OFFSET + T_NUMBER_SIZE_BYTES;
+
+    /// Record for methods which do not narrow down an interval
+    pub fn without_bracket(point: Point, step: TNumber) -> Self {
+        Self {
+            point,
+            step,
+            left: TNumber::NAN,
+            right: TNumber::NAN,
+        }
+    }
+
+    /// Marks the end of trace
+    pub fn terminator() -> Self {
+        Self::without_bracket(Point::new(TNumber::NAN, TNumber::NAN), TNumber::NAN)
+    }
+
+    pub fn is_terminator(&self) -> bool {
+        self.point.x.is_nan()
+    }
+}
+
+impl ByteSerializable<{ Self::TRACE_RECORD_SIZE }> for TraceRecord {
+    fn to_bytes(&self) -> [u8; Self::TRACE_RECORD_SIZE] {
+        let mut bytes = [0; Self::TRACE_RECORD_SIZE];
+        bytes[Self::POINT_OFFSET..Self::STEP_OFFSET].copy_from_slice(&self.point.to_bytes());
+        bytes[Self::STEP_OFFSET..Self::LEFT_OFFSET].copy_from_slice(&self.step.to_le_bytes());
+        bytes[Self::LEFT_OFFSET..Self::RIGHT_OFFSET].copy_from_slice(&self.left.to_le_bytes());
+        bytes[Self::RIGHT_OFFSET..].copy_from_slice(&self.right.to_le_bytes());
+
+        bytes
+    }
+
+    fn try_from_bytes(raw_bytes: &[u8; Self::TRACE_RECORD_SIZE]) -> Result<Self, DecodeError> {
+        Ok(Self {
+            point: Point::try_from_bytes(&read_field(raw_bytes, Self::POINT_OFFSET))?,
+            step: TNumber::from_le_bytes(read_field(raw_bytes, Self::STEP_OFFSET)),
+            left: TNumber::from_le_bytes(read_field(raw_bytes, Self::LEFT_OFFSET)),
+            right: TNumber::from_le_bytes(read_field(raw_bytes, Self::RIGHT_OFFSET)),
+        })
     }
 }
@@ -0,0 +1,30 @@
+//! Functions math traits are implemented with. Both backends support `f32` only
+
+#[cfg(target_arch = "avr")]
+mod avr {
+    pub fn pow(base: f32, power: f32) -> f32 {
+        unsafe { avr_libc::powf(base, power) }
+    }
+
+    pub fn sin(x: f32) -> f32 {
+        unsafe { avr_libc::sinf(x) }
+    }
+
+    pub fn cos(x: f32) -> f32 {
+        unsafe { avr_libc::cosf(x) }
+    }
+
+    pub fn ln(x: f32) -> f32 {
+        unsafe { avr_libc::logf(x) }
+    }
+
+    pub fn exp(x: f32) -> f32 {
+        unsafe { avr_libc::expf(x) }
+    }
+}
+
+#[cfg(target_arch = "avr")]
+pub use avr::*;
+
+#[cfg(not(target_arch = "avr"))]
+pub use libm::{cosf as cos, expf as exp, logf as ln, powf as pow, sinf as sin};
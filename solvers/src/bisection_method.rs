@@ -0,0 +1,63 @@
+use protocol::point::Point;
+use protocol::response::{ComputeRootResponse, TraceRecord};
+use protocol::TNumber;
+
+use super::Abs;
+use super::{ensure_finite, MethodError, NonLinearEquation, Solver, SolverInput, Tracer};
+
+/// Halves the interval until it is precise enough. Width of the interval
+/// is used as a step. Converges whenever the function changes its sign within the interval
+pub struct BisectionSolver;
+
+impl Solver<NonLinearEquation> for BisectionSolver {
+    fn solve_traced(
+        &self,
+        equation: &NonLinearEquation,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let SolverInput {
+            mut start, mut end, ..
+        } = *parameters;
+
+        let mut start_value = (equation.function)(start);
+        // there is no previous approximation before the first iteration,
+        // so its step is the width of the bracket
+        let mut x_previous: Option<TNumber> = None;
+        for iteration in 1..=parameters.max_iterations {
+            let x = (start + end) / 2.;
+            let y = (equation.function)(x);
+            ensure_finite(Point::new(x, y))?;
+
+            let step = match x_previous {
+                Some(previous) => Abs::abs(x - previous),
+                None => Abs::abs(end - start),
+            };
+            trace(TraceRecord {
+                point: Point::new(x, y),
+                step,
+                left: start,
+                right: end,
+            });
+            x_previous = Some(x);
+
+            if parameters.is_precise(Abs::abs(end - start), Abs::abs(y)) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(x, y),
+                    iterations: iteration,
+                    step,
+                    residual: Abs::abs(y),
+                });
+            }
+
+            if y.is_sign_negative() == start_value.is_sign_negative() {
+                start = x;
+                start_value = y;
+            } else {
+                end = x;
+            }
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
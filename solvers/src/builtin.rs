@@ -0,0 +1,56 @@
+//! Equations shipped with the firmware. Simulated device offers the same ones
+
+use protocol::point::PointCoordinate;
+use protocol::TNumber;
+
+use crate::system_of_equations::{EquationWithPhi, SystemOfEquations};
+use crate::{GenericEquation, NonLinearEquation, Number, Pow, Trigonometry};
+
+struct SquarePlusSine;
+
+impl GenericEquation for SquarePlusSine {
+    const NAME: &'static str = "x^2 + x + sin(x)";
+
+    fn function<T: Number>(x: T) -> T {
+        x.pow(T::from(2.)) + x + x.sin()
+    }
+}
+
+struct ShiftedLogarithm;
+
+impl GenericEquation for ShiftedLogarithm {
+    const NAME: &'static str = "ln(x + 15)";
+
+    fn function<T: Number>(x: T) -> T {
+        (x + 15.).ln()
+    }
+}
+
+pub const SQUARE_PLUS_SINE: NonLinearEquation = NonLinearEquation {
+    second_derivative: Some(|x: TNumber| 2. - Trigonometry::sin(x)),
+    ..NonLinearEquation::generic::<SquarePlusSine>()
+};
+
+pub const SHIFTED_LOGARITHM: NonLinearEquation = NonLinearEquation {
+    second_derivative: Some(|x: TNumber| -1. / (x + 15.).pow(2.)),
+    ..NonLinearEquation::generic::<ShiftedLogarithm>()
+};
+
+pub const SINE_AND_COSINE: SystemOfEquations = SystemOfEquations {
+    first: EquationWithPhi {
+        name: "y = 1 - sin(x) / 2",
+        function: |x| (1. - Trigonometry::sin(x) / 2., PointCoordinate::Y),
+        phi: |(_x, y)| 0.7 - Trigonometry::cos(y - 1.),
+        phi_gradient: |(_x, y)| (0., Trigonometry::sin(y - 1.)),
+        residual: |(x, y)| 1. - Trigonometry::sin(x) / 2. - y,
+        gradient: |(x, _y)| (-Trigonometry::cos(x) / 2., -1.),
+    },
+    second: EquationWithPhi {
+        name: "x = 0.7 - cos(y - 1)",
+        function: |y| (0.7 - Trigonometry::cos(y - 1.), PointCoordinate::X),
+        phi: |(x, _y)| 1. - Trigonometry::sin(x) / 2.,
+        phi_gradient: |(x, _y)| (-Trigonometry::cos(x) / 2., 0.),
+        residual: |(x, y)| 0.7 - Trigonometry::cos(y - 1.) - x,
+        gradient: |(_x, y)| (-1., Trigonometry::sin(y - 1.)),
+    },
+};
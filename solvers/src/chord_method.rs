@@ -0,0 +1,71 @@
+use protocol::point::Point;
+use protocol::response::{ComputeRootResponse, TraceRecord};
+use protocol::TNumber;
+
+use crate::MethodError;
+
+use super::Abs;
+use super::{ensure_finite, NonLinearEquation, Solver, SolverInput, Tracer};
+
+pub struct ChordSolver;
+
+impl Solver<NonLinearEquation> for ChordSolver {
+    fn solve_traced(
+        &self,
+        equation: &NonLinearEquation,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let SolverInput {
+            mut start, mut end, ..
+        } = *parameters;
+
+        // there is no previous approximation before the first iteration,
+        // so its step is the width of the bracket
+        let mut x_previous: Option<TNumber> = None;
+        for iteration in 1..=parameters.max_iterations {
+            let start_value = (equation.function)(start);
+            let end_value = (equation.function)(end);
+            if end_value == start_value {
+                return Err(MethodError::ZeroDenominator);
+            }
+
+            let x = start - (start_value / (end_value - start_value)) * (end - start);
+            let y = (equation.function)(x);
+            ensure_finite(Point::new(x, y))?;
+
+            let step = match x_previous {
+                Some(previous) => Abs::abs(x - previous),
+                None => Abs::abs(end - start),
+            };
+            trace(TraceRecord {
+                point: Point::new(x, y),
+                step,
+                left: start,
+                right: end,
+            });
+            x_previous = Some(x);
+
+            if parameters.is_precise(step, Abs::abs(y)) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(x, y),
+                    iterations: iteration,
+                    step,
+                    residual: Abs::abs(y),
+                });
+            }
+
+            if !(start <= x && x <= end) {
+                return Err(MethodError::Diverges);
+            }
+
+            if y * end_value > 0. {
+                end = x;
+            } else {
+                start = x;
+            }
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
@@ -0,0 +1,183 @@
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use protocol::TNumber;
+
+use super::{Exponent, Logarithm, Pow, Trigonometry};
+
+/// *a + b ε* where *ε² = 0*. Function evaluated at *x + ε* yields
+/// *f(x) + f'(x) ε*, so derivative is computed along with the value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dual {
+    pub value: TNumber,
+    pub derivative: TNumber,
+}
+
+impl Dual {
+    /// Argument of the function being differentiated
+    pub fn variable(x: TNumber) -> Self {
+        Self {
+            value: x,
+            derivative: 1.,
+        }
+    }
+
+    pub fn constant(value: TNumber) -> Self {
+        Self {
+            value,
+            derivative: 0.,
+        }
+    }
+
+    /// Chain rule: `outer_derivative` is derivative of the outer function at `self.value`
+    fn chain(self, value: TNumber, outer_derivative: TNumber) -> Self {
+        Self {
+            value,
+            derivative: outer_derivative * self.derivative,
+        }
+    }
+}
+
+impl From<TNumber> for Dual {
+    fn from(value: TNumber) -> Self {
+        Self::constant(value)
+    }
+}
+
+impl Add for Dual {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            value: self.value + other.value,
+            derivative: self.derivative + other.derivative,
+        }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            value: self.value - other.value,
+            derivative: self.derivative - other.derivative,
+        }
+    }
+}
+
+impl Mul for Dual {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            value: self.value * other.value,
+            derivative: self.derivative * other.value + self.value * other.derivative,
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self {
+            value: self.value / other.value,
+            derivative: (self.derivative * other.value - self.value * other.derivative)
+                / (other.value * other.value),
+        }
+    }
+}
+
+impl Neg for Dual {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            value: -self.value,
+            derivative: -self.derivative,
+        }
+    }
+}
+
+impl Add<TNumber> for Dual {
+    type Output = Self;
+
+    fn add(self, other: TNumber) -> Self {
+        self + Self::constant(other)
+    }
+}
+
+impl Sub<TNumber> for Dual {
+    type Output = Self;
+
+    fn sub(self, other: TNumber) -> Self {
+        self - Self::constant(other)
+    }
+}
+
+impl Mul<TNumber> for Dual {
+    type Output = Self;
+
+    fn mul(self, other: TNumber) -> Self {
+        Self {
+            value: self.value * other,
+            derivative: self.derivative * other,
+        }
+    }
+}
+
+impl Div<TNumber> for Dual {
+    type Output = Self;
+
+    fn div(self, other: TNumber) -> Self {
+        Self {
+            value: self.value / other,
+            derivative: self.derivative / other,
+        }
+    }
+}
+
+impl Pow for Dual {
+    fn pow(self, power: Self) -> Self {
+        let value = self.value.pow(power.value);
+        // power rule holds for negative base as well
+        if power.derivative == 0. {
+            let outer_derivative = power.value * self.value.pow(power.value - 1.);
+            return self.chain(value, outer_derivative);
+        }
+
+        // u^v = exp(v ln u)
+        Self {
+            value,
+            derivative: value
+                * (power.derivative * Logarithm::ln(self.value)
+                    + power.value * self.derivative / self.value),
+        }
+    }
+}
+
+impl Trigonometry for Dual {
+    fn sin(self) -> Self {
+        self.chain(Trigonometry::sin(self.value), Trigonometry::cos(self.value))
+    }
+
+    fn cos(self) -> Self {
+        self.chain(
+            Trigonometry::cos(self.value),
+            -Trigonometry::sin(self.value),
+        )
+    }
+}
+
+impl Logarithm for Dual {
+    fn ln(self) -> Self {
+        self.chain(Logarithm::ln(self.value), 1. / self.value)
+    }
+}
+
+impl Exponent for Dual {
+    fn exp(self) -> Self {
+        let value = Exponent::exp(self.value);
+        self.chain(value, value)
+    }
+}
@@ -0,0 +1,459 @@
+//! Methods solving equations along with the math they are written in.
+//! Math backend is chosen by target: avr-libc on arduino, libm elsewhere,
+//! so methods are tested on host
+
+#![no_std]
+
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use protocol::decode_error::DecodeError;
+use protocol::point::{Point, PointCoordinate};
+use protocol::request::compute_method::{Method, SystemMethod};
+use protocol::request::payloads::{ComputeRootPayload, StoppingCriterion};
+use protocol::request::{EquationMode, EquationModeRaw, RequestPackage, SingleEquation};
+use protocol::response::{
+    ComputeRootResponse, InitialApproximationsResponse, MethodError, TraceRecord,
+};
+use protocol::TNumber;
+
+use crate::system_of_equations::{
+    NewtonSolverForSystems, SimpleIteratorSolverForSystems, SystemOfEquations,
+};
+
+pub const LEFT_BORDER: TNumber = -10.;
+pub const RIGHT_BORDER: TNumber = 10.;
+pub const POINT_INTERVAL_LENGTH: TNumber = (RIGHT_BORDER - LEFT_BORDER) / POINT_AMOUNT as TNumber;
+pub use protocol::POINT_AMOUNT;
+
+mod backend;
+mod bisection_method;
+pub mod builtin;
+mod chord_method;
+mod dual;
+mod newton_method;
+mod secant_method;
+mod simple_iteration_method;
+pub mod system_of_equations;
+pub mod user_equation;
+
+pub use bisection_method::BisectionSolver;
+pub use chord_method::ChordSolver;
+pub use dual::Dual;
+pub use newton_method::NewtonSolver;
+pub use secant_method::SecantSolver;
+pub use simple_iteration_method::SimpleIterationSolver;
+
+pub struct SolverInput {
+    pub start: TNumber,
+    pub end: TNumber,
+    pub epsilon: TNumber,
+    pub max_iterations: u16,
+    pub criterion: StoppingCriterion,
+}
+
+impl SolverInput {
+    /// Checks stopping criterion requested by host.
+    /// `step` is method's estimate of error, `residual` is *|f(x)|*
+    pub fn is_precise(&self, step: TNumber, residual: TNumber) -> bool {
+        // NaN is never precise
+        let is_step_precise = step <= self.epsilon;
+        let is_residual_precise = residual <= self.epsilon;
+        match self.criterion {
+            StoppingCriterion::Step => is_step_precise,
+            StoppingCriterion::Residual => is_residual_precise,
+            StoppingCriterion::Both => is_step_precise && is_residual_precise,
+        }
+    }
+}
+
+/// Bracket device accepts: both ends are within the plotted range, in order
+pub fn is_bracket_valid(left: TNumber, right: TNumber) -> bool {
+    let range = LEFT_BORDER..RIGHT_BORDER;
+    range.contains(&left) && range.contains(&right) && left < right
+}
+
+pub fn check_roots_in_range(
+    equation: &NonLinearEquation,
+    parameters: &SolverInput,
+) -> Result<(), MethodError> {
+    let different_signs = (equation.function)(parameters.start).is_sign_negative()
+        != (equation.function)(parameters.end).is_sign_negative();
+    let derevative_different_signs = !is_monotonic(equation, parameters.start, parameters.end);
+
+    let suggestion = || suggest_bracket(equation, (parameters.start + parameters.end) / 2.);
+
+    // using heurisctics to guess amount of roots withing range
+    match (different_signs, derevative_different_signs) {
+        (true, false | true) => Ok(()),
+        (false, true) => Err(MethodError::MoreThanOneRootInRange {
+            suggestion: suggestion(),
+        }),
+        (false, false) => Err(MethodError::NoRootInRange {
+            suggestion: suggestion(),
+        }),
+    }
+}
+
+/// Derivative keeps its sign within range
+fn is_monotonic(equation: &NonLinearEquation, start: TNumber, end: TNumber) -> bool {
+    const SAMPLES_AMOUNT: usize = 100;
+
+    let mut x = start;
+    let is_first_negative = (equation.first_derivative)(x).is_sign_negative();
+    let step = (end - start) / SAMPLES_AMOUNT as f32;
+    for _ in 0..SAMPLES_AMOUNT {
+        x += step;
+        let y = (equation.first_derivative)(x);
+        if y.is_sign_negative() != is_first_negative {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Calls `found` with borders of every cell of function points grid
+/// where function changes its sign
+pub fn for_each_sign_change(equation: &NonLinearEquation, mut found: impl FnMut(TNumber, TNumber)) {
+    let mut start = LEFT_BORDER;
+    let mut start_value = (equation.function)(start);
+    // the last cell ends at `RIGHT_BORDER`
+    for index in 1..=POINT_AMOUNT {
+        let end = LEFT_BORDER + POINT_INTERVAL_LENGTH * index as TNumber;
+        let end_value = (equation.function)(end);
+        // function is not defined everywhere within range
+        let is_root_isolated = start_value.is_finite()
+            && end_value.is_finite()
+            && start_value.is_sign_negative() != end_value.is_sign_negative();
+
+        if is_root_isolated {
+            found(start, end);
+        }
+
+        start = end;
+        start_value = end_value;
+    }
+}
+
+/// Bracket with a single sign change and monotonic function,
+/// which is the nearest to `center`
+fn suggest_bracket(
+    equation: &NonLinearEquation,
+    center: TNumber,
+) -> Option<InitialApproximationsResponse> {
+    let mut nearest: Option<(TNumber, InitialApproximationsResponse)> = None;
+    for_each_sign_change(equation, |left, right| {
+        if !is_monotonic(equation, left, right) {
+            return;
+        }
+
+        let distance = Abs::abs((left + right) / 2. - center);
+        if nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance) {
+            nearest = Some((distance, InitialApproximationsResponse { left, right }));
+        }
+    });
+
+    nearest.map(|(_, bracket)| bracket)
+}
+
+pub type SingleArgFunction = fn(x: TNumber) -> TNumber;
+
+#[derive(Clone)]
+pub struct NonLinearEquation {
+    /// Human readable form. Displayed by host
+    pub name: &'static str,
+    pub function: SingleArgFunction,
+    /// Computed with [`Dual`] numbers for [`GenericEquation`]s
+    pub first_derivative: SingleArgFunction,
+    /// Lets Newton's method pick starting point it converges from
+    pub second_derivative: Option<SingleArgFunction>,
+}
+
+/// Equation written once for both plain and [`Dual`] numbers,
+/// so its derivative does not have to be written by hand
+pub trait GenericEquation {
+    /// Human readable form. Displayed by host
+    const NAME: &'static str;
+
+    fn function<T: Number>(x: T) -> T;
+}
+
+impl NonLinearEquation {
+    pub const fn generic<E: GenericEquation>() -> Self {
+        Self {
+            name: E::NAME,
+            function: E::function::<TNumber>,
+            first_derivative: dual_derivative::<E>,
+            second_derivative: None,
+        }
+    }
+}
+
+fn dual_derivative<E: GenericEquation>(x: TNumber) -> TNumber {
+    E::function(Dual::variable(x)).derivative
+}
+
+#[derive(Clone, Copy)]
+pub struct Equations {
+    pub single: &'static [NonLinearEquation],
+    pub systems: &'static [SystemOfEquations],
+}
+
+impl Equations {
+    /// Host may refer to equation which is not flashed, e.g. an older catalogue.
+    /// Index is checked before it is used
+    pub fn check_request(&self, request: &RequestPackage) -> Result<(), DecodeError> {
+        let (mode, index) = match *request {
+            RequestPackage::FunctionPoints { payload } => (payload.mode, payload.index),
+            RequestPackage::ComputeRoot { payload }
+            | RequestPackage::ComputeRootTrace { payload }
+            | RequestPackage::FindAllRoots { payload } => match payload.mode {
+                EquationMode::Single(SingleEquation {
+                    equation_number, ..
+                }) => (EquationModeRaw::SingleEquation, equation_number),
+                EquationMode::SystemOfEquations { system_number, .. } => {
+                    (EquationModeRaw::SystemOfEquations, system_number)
+                }
+            },
+            _ => return Ok(()),
+        };
+
+        match mode {
+            EquationModeRaw::SingleEquation if index as usize >= self.single.len() => {
+                Err(DecodeError::UnknownEquation(index))
+            }
+            EquationModeRaw::SystemOfEquations if index as usize >= self.systems.len() => {
+                Err(DecodeError::UnknownSystem(index))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Solves equation or system chosen by host within `approximations`.
+    /// Request has to pass [`check_request`](Equations::check_request)
+    pub fn compute_root(
+        &self,
+        payload: ComputeRootPayload,
+        approximations: InitialApproximationsResponse,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let parameters = SolverInput {
+            start: approximations.left,
+            end: approximations.right,
+            epsilon: payload.epsilon,
+            max_iterations: payload.max_iterations,
+            criterion: payload.criterion,
+        };
+
+        match payload.mode {
+            EquationMode::Single(SingleEquation {
+                method,
+                equation_number,
+            }) => {
+                let equation = &self.single[equation_number as usize];
+
+                check_roots_in_range(equation, &parameters)?;
+                single_equation_solver(method).solve_traced(equation, &parameters, trace)
+            }
+            EquationMode::SystemOfEquations {
+                method,
+                system_number,
+            } => {
+                let system = &self.systems[system_number as usize];
+                let solver: &dyn Solver<SystemOfEquations> = match method {
+                    SystemMethod::SimpleIteration => &SimpleIteratorSolverForSystems,
+                    SystemMethod::Newton => &NewtonSolverForSystems,
+                };
+
+                solver.solve_traced(system, &parameters, trace)
+            }
+        }
+    }
+
+    /// Writes back result per root isolated within the whole range.
+    /// Request has to pass [`check_request`](Equations::check_request)
+    pub fn find_all_roots(
+        &self,
+        payload: ComputeRootPayload,
+        write_back: &mut dyn FnMut(Result<ComputeRootResponse, MethodError>),
+    ) {
+        // roots of systems are not isolated by sign changes
+        let EquationMode::Single(SingleEquation {
+            method,
+            equation_number,
+        }) = payload.mode
+        else {
+            return;
+        };
+        let equation = &self.single[equation_number as usize];
+        let solver = single_equation_solver(method);
+
+        for_each_sign_change(equation, |start, end| {
+            let parameters = SolverInput {
+                start,
+                end,
+                epsilon: payload.epsilon,
+                max_iterations: payload.max_iterations,
+                criterion: payload.criterion,
+            };
+            write_back(solver.solve(equation, &parameters));
+        });
+    }
+}
+
+pub fn single_equation_solver(method: Method) -> &'static dyn Solver<NonLinearEquation> {
+    match method {
+        Method::Chord => &ChordSolver,
+        Method::Secant => &SecantSolver,
+        Method::Newton => &NewtonSolver,
+        Method::Bisection => &BisectionSolver,
+        Method::SimpleIterationSingle => &SimpleIterationSolver,
+    }
+}
+
+/// Evaluates `function` on the grid of [`POINT_AMOUNT`] points within range.
+/// `function` tells which coordinate it has computed
+pub fn for_each_point(
+    function: &mut dyn FnMut(TNumber) -> (TNumber, PointCoordinate),
+    write_back: &mut dyn FnMut(Point),
+) {
+    for index in 0..POINT_AMOUNT {
+        let variable = LEFT_BORDER + POINT_INTERVAL_LENGTH * index as TNumber;
+        let (dependent, coord) = function(variable);
+        let point = match coord {
+            PointCoordinate::X => Point::new(dependent, variable),
+            PointCoordinate::Y => Point::new(variable, dependent),
+        };
+
+        write_back(point);
+    }
+}
+
+/// Fails once function is evaluated out of its domain or iterations overflow.
+/// Checked before tracing: record with NaN would be taken for terminator
+pub(crate) fn ensure_finite(point: Point) -> Result<(), MethodError> {
+    match point.x.is_finite() && point.y.is_finite() {
+        true => Ok(()),
+        false => Err(MethodError::NotFinite),
+    }
+}
+
+/// Receives state of the method after each iteration
+pub type Tracer<'a> = &'a mut dyn FnMut(TraceRecord);
+
+pub trait Solver<T> {
+    /// Calls `trace` upon each iteration
+    fn solve_traced(
+        &self,
+        equation: &T,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError>;
+
+    fn solve(
+        &self,
+        equation: &T,
+        parameters: &SolverInput,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        self.solve_traced(equation, parameters, &mut |_| ())
+    }
+}
+
+/// Everything equation may be evaluated on. Constants are
+/// either the right operand or converted with `T::from`
+pub trait Number:
+    Copy
+    + From<TNumber>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + Add<TNumber, Output = Self>
+    + Sub<TNumber, Output = Self>
+    + Mul<TNumber, Output = Self>
+    + Div<TNumber, Output = Self>
+    + Pow
+    + Trigonometry
+    + Logarithm
+    + Exponent
+{
+}
+
+impl<T> Number for T where
+    T: Copy
+        + From<TNumber>
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + Div<Output = T>
+        + Neg<Output = T>
+        + Add<TNumber, Output = T>
+        + Sub<TNumber, Output = T>
+        + Mul<TNumber, Output = T>
+        + Div<TNumber, Output = T>
+        + Pow
+        + Trigonometry
+        + Logarithm
+        + Exponent
+{
+}
+
+pub trait Abs {
+    fn abs(self) -> Self;
+}
+
+pub trait Pow {
+    fn pow(self, power: Self) -> Self;
+}
+
+impl Pow for TNumber {
+    fn pow(self, power: TNumber) -> Self {
+        backend::pow(self, power)
+    }
+}
+
+impl Abs for TNumber {
+    fn abs(self) -> TNumber {
+        if self.is_sign_positive() {
+            return self;
+        }
+
+        -self
+    }
+}
+
+pub trait Trigonometry {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+}
+
+impl Trigonometry for TNumber {
+    fn sin(self) -> Self {
+        backend::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        backend::cos(self)
+    }
+}
+
+pub trait Logarithm {
+    fn ln(self) -> Self;
+}
+
+impl Logarithm for TNumber {
+    fn ln(self) -> Self {
+        backend::ln(self)
+    }
+}
+
+pub trait Exponent {
+    fn exp(self) -> Self;
+}
+
+impl Exponent for TNumber {
+    fn exp(self) -> Self {
+        backend::exp(self)
+    }
+}
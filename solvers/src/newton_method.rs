@@ -0,0 +1,69 @@
+use protocol::point::Point;
+use protocol::response::{ComputeRootResponse, TraceRecord};
+use protocol::TNumber;
+
+use super::Abs;
+use super::{ensure_finite, MethodError, NonLinearEquation, Solver, SolverInput, Tracer};
+
+pub struct NewtonSolver;
+
+impl Solver<NonLinearEquation> for NewtonSolver {
+    fn solve_traced(
+        &self,
+        equation: &NonLinearEquation,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let mut x = starting_point(equation, parameters);
+        for iteration in 1..=parameters.max_iterations {
+            let derivative = (equation.first_derivative)(x);
+            // NaN passes the check below and would be taken for divergence
+            if !derivative.is_finite() {
+                return Err(MethodError::NotFinite);
+            }
+
+            // tangent is parallel to x axis: it never crosses zero
+            if derivative == 0. {
+                return Err(MethodError::ZeroDenominator);
+            }
+
+            let x_next = x - (equation.function)(x) / derivative;
+
+            if !(parameters.start <= x_next && x_next <= parameters.end) {
+                return Err(MethodError::Diverges);
+            }
+
+            let step = Abs::abs(x_next - x);
+            x = x_next;
+
+            let y = (equation.function)(x);
+            ensure_finite(Point::new(x, y))?;
+            trace(TraceRecord::without_bracket(Point::new(x, y), step));
+
+            if parameters.is_precise(step, Abs::abs(y)) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(x, y),
+                    iterations: iteration,
+                    step,
+                    residual: Abs::abs(y),
+                });
+            }
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
+
+/// Border where *f(x) * f''(x) > 0*: tangents from there approach the root
+/// monotonically. Middle of the interval if neither border is such
+fn starting_point(equation: &NonLinearEquation, parameters: &SolverInput) -> TNumber {
+    let middle = (parameters.start + parameters.end) / 2.;
+    let Some(second_derivative) = equation.second_derivative else {
+        return middle;
+    };
+
+    [parameters.start, parameters.end]
+        .into_iter()
+        .find(|&x| (equation.function)(x) * second_derivative(x) > 0.)
+        .unwrap_or(middle)
+}
@@ -0,0 +1,49 @@
+use protocol::point::Point;
+use protocol::response::{ComputeRootResponse, TraceRecord};
+
+use super::Abs;
+use super::{ensure_finite, MethodError, NonLinearEquation, Solver, SolverInput, Tracer};
+
+pub struct SecantSolver;
+
+impl Solver<NonLinearEquation> for SecantSolver {
+    fn solve_traced(
+        &self,
+        equation: &NonLinearEquation,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let length = parameters.end - parameters.start;
+        let mut x_previous = parameters.start + length / 4.;
+        let mut x = parameters.end - length / 4.;
+        for iteration in 1..=parameters.max_iterations {
+            let value = (equation.function)(x);
+            let value_previous = (equation.function)(x_previous);
+            if value == value_previous {
+                return Err(MethodError::ZeroDenominator);
+            }
+
+            let x_next = x - ((x - x_previous) / (value - value_previous)) * value;
+
+            x_previous = x;
+            x = x_next;
+
+            let y = (equation.function)(x);
+            ensure_finite(Point::new(x, y))?;
+
+            let step = Abs::abs(x - x_previous);
+            trace(TraceRecord::without_bracket(Point::new(x, y), step));
+
+            if parameters.is_precise(step, Abs::abs(y)) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(x, y),
+                    iterations: iteration,
+                    step,
+                    residual: Abs::abs(y),
+                });
+            }
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
@@ -1,26 +1,25 @@
 use protocol::point::Point;
+use protocol::response::{ComputeRootResponse, TraceRecord};
 use protocol::TNumber;
 
+use super::ensure_finite;
 use super::Abs;
 use super::MethodError;
 use super::NonLinearEquation;
 use super::Solver;
 use super::SolverInput;
-use super::MAX_ITERATIONS;
+use super::Tracer;
 
 pub struct SimpleIterationSolver;
 
 impl Solver<NonLinearEquation> for SimpleIterationSolver {
-    fn solve(
+    fn solve_traced(
         &self,
         equation: &NonLinearEquation,
         parameters: &SolverInput,
-    ) -> Result<Point, MethodError> {
-        let &SolverInput {
-            start,
-            end,
-            epsilon,
-        } = parameters;
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let &SolverInput { start, end, .. } = parameters;
         let lambda = calculate_lambda(equation, parameters);
         let phi = |x| x + lambda * (equation.function)(x);
         let phi_derivative = |x| 1. + lambda * (equation.first_derivative)(x);
@@ -32,16 +31,30 @@ impl Solver<NonLinearEquation> for SimpleIterationSolver {
 
         let mut x = (start + end) / 2.;
 
-        for _ in 0..MAX_ITERATIONS {
+        for iteration in 1..=parameters.max_iterations {
             let next_x = phi(x);
-            if is_precise(x, next_x, q, epsilon) {
-                return Ok(Point::new(next_x, (equation.function)(next_x)));
+            let next_y = (equation.function)(next_x);
+            ensure_finite(Point::new(next_x, next_y))?;
+
+            let step = Abs::abs(next_x - x);
+            trace(TraceRecord::without_bracket(
+                Point::new(next_x, next_y),
+                step,
+            ));
+
+            if parameters.is_precise(estimate_error(x, next_x, q), Abs::abs(next_y)) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(next_x, next_y),
+                    iterations: iteration,
+                    step,
+                    residual: Abs::abs(next_y),
+                });
             }
 
             x = next_x;
         }
 
-        return Err(MethodError::Diverges);
+        Err(MethodError::IterationLimitReached)
     }
 }
 
@@ -64,11 +77,13 @@ fn calculate_lambda(
         )
 }
 
-fn is_precise(x: TNumber, next_x: TNumber, q: TNumber, epsilon: TNumber) -> bool {
+/// Distance to the root is within *q / (1 - q) * |x_k - x_{k-1}|*.
+/// Step itself is good enough estimate for quickly converging iterations
+fn estimate_error(x: TNumber, next_x: TNumber, q: TNumber) -> TNumber {
     let difference = Abs::abs(x - next_x);
     if q <= 0.5 {
-        return difference < epsilon;
+        return difference;
     }
 
-    return difference < ((1. - q) / q * epsilon);
+    difference * q / (1. - q)
 }
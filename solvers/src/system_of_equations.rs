@@ -0,0 +1,195 @@
+use protocol::{
+    point::{Point, PointCoordinate},
+    response::{ComputeRootResponse, MethodError, TraceRecord},
+    TNumber,
+};
+
+use crate::{ensure_finite, Abs, Solver, SolverInput, Tracer};
+
+#[derive(Clone)]
+pub struct EquationWithPhi {
+    /// Human readable form. Displayed by host
+    pub name: &'static str,
+    /// To generate points for function graph.
+    /// Use point coordinate to distinguish between
+    /// *f(x) = y* and *f(y) = x*
+    /// `PointCoordinate` designates, which coordinate is dependent.
+    ///
+    /// Example:
+    /// `(3., PointCoordinate::x)` is returned for f(y) = x
+    pub function: fn(TNumber) -> (TNumber, PointCoordinate),
+    /// Calculate next step in simple iteration algorithm
+    pub phi: fn((TNumber, TNumber)) -> TNumber,
+    /// Partial derivatives of `phi`: *(dphi/dx, dphi/dy)*.
+    /// Used to check convergence of simple iteration algorithm
+    pub phi_gradient: fn((TNumber, TNumber)) -> (TNumber, TNumber),
+    /// Equation in the form of *F(x, y) = 0*. Returns *F(x, y)*
+    pub residual: fn((TNumber, TNumber)) -> TNumber,
+    /// Partial derivatives of `residual`: *(dF/dx, dF/dy)*
+    pub gradient: fn((TNumber, TNumber)) -> (TNumber, TNumber),
+}
+
+#[derive(Clone)]
+pub struct SystemOfEquations {
+    pub first: EquationWithPhi,
+    pub second: EquationWithPhi,
+}
+
+impl SystemOfEquations {
+    /// Jacobian matrix of the system at the `point`.
+    /// Each row holds partial derivatives of corresponding equation
+    pub fn jacobian(&self, point: (TNumber, TNumber)) -> [[TNumber; 2]; 2] {
+        let (first_dx, first_dy) = (self.first.gradient)(point);
+        let (second_dx, second_dy) = (self.second.gradient)(point);
+
+        [[first_dx, first_dy], [second_dx, second_dy]]
+    }
+
+    /// Largest of absolute residuals of both equations
+    fn residual(&self, point: (TNumber, TNumber)) -> TNumber {
+        TNumber::max(
+            Abs::abs((self.first.residual)(point)),
+            Abs::abs((self.second.residual)(point)),
+        )
+    }
+
+    /// Norm of phi's jacobian matrix at the `point`.
+    /// Maximum of absolute row sums is used
+    fn phi_jacobian_norm(&self, point: (TNumber, TNumber)) -> TNumber {
+        let (first_dx, first_dy) = (self.first.phi_gradient)(point);
+        let (second_dx, second_dy) = (self.second.phi_gradient)(point);
+
+        TNumber::max(
+            Abs::abs(first_dx) + Abs::abs(first_dy),
+            Abs::abs(second_dx) + Abs::abs(second_dy),
+        )
+    }
+}
+
+/// Simple iteration converges if norm of phi's jacobian is
+/// less than 1 within the area iterations happen in.
+/// Area is estimated as square built on the bracket: both
+/// `x` and `y` run from `start` to `end`.
+fn estimate_convergence_coefficient(
+    system: &SystemOfEquations,
+    &SolverInput { start, end, .. }: &SolverInput,
+) -> TNumber {
+    const SAMPLES_AMOUNT: usize = 4;
+
+    let step = (end - start) / SAMPLES_AMOUNT as TNumber;
+    let mut q: TNumber = 0.;
+    for row in 0..=SAMPLES_AMOUNT {
+        for column in 0..=SAMPLES_AMOUNT {
+            let point = (
+                start + step * column as TNumber,
+                start + step * row as TNumber,
+            );
+            q = TNumber::max(q, system.phi_jacobian_norm(point));
+        }
+    }
+
+    q
+}
+
+pub struct SimpleIteratorSolverForSystems;
+
+impl Solver<SystemOfEquations> for SimpleIteratorSolverForSystems {
+    fn solve_traced(
+        &self,
+        system: &SystemOfEquations,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        // Amount of consecutive iterations with growing step
+        // after which method is considered diverging
+        const GROWING_STEPS_LIMIT: usize = 5;
+
+        if estimate_convergence_coefficient(system, parameters) >= 1. {
+            return Err(MethodError::ConvergenceConditionNotMet);
+        }
+
+        let mut x = (parameters.start, parameters.end);
+        let mut previous_step = TNumber::INFINITY;
+        let mut growing_steps: usize = 0;
+        for iteration in 1..=parameters.max_iterations {
+            let new_x = ((system.first.phi)(x), (system.second.phi)(x));
+            ensure_finite(Point::new(new_x.0, new_x.1))?;
+
+            let step = TNumber::max(Abs::abs(new_x.0 - x.0), Abs::abs(new_x.1 - x.1));
+            trace(TraceRecord::without_bracket(
+                Point::new(new_x.0, new_x.1),
+                step,
+            ));
+
+            let residual = system.residual(new_x);
+            if parameters.is_precise(step, residual) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(new_x.0, new_x.1),
+                    iterations: iteration,
+                    step,
+                    residual,
+                });
+            }
+
+            growing_steps = if step > previous_step {
+                growing_steps + 1
+            } else {
+                0
+            };
+
+            if growing_steps >= GROWING_STEPS_LIMIT {
+                return Err(MethodError::Diverges);
+            }
+
+            previous_step = step;
+            x = new_x;
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
+
+pub struct NewtonSolverForSystems;
+
+impl Solver<SystemOfEquations> for NewtonSolverForSystems {
+    fn solve_traced(
+        &self,
+        system: &SystemOfEquations,
+        parameters: &SolverInput,
+        trace: Tracer,
+    ) -> Result<ComputeRootResponse, MethodError> {
+        let mut x = (parameters.start, parameters.end);
+        for iteration in 1..=parameters.max_iterations {
+            let [[a, b], [c, d]] = system.jacobian(x);
+            let determinant = a * d - b * c;
+            if determinant == 0. {
+                return Err(MethodError::ZeroDenominator);
+            }
+
+            // solve J * delta = -F by Cramer's rule
+            let (first, second) = (-(system.first.residual)(x), -(system.second.residual)(x));
+            let delta = (
+                (first * d - b * second) / determinant,
+                (a * second - c * first) / determinant,
+            );
+
+            x = (x.0 + delta.0, x.1 + delta.1);
+            ensure_finite(Point::new(x.0, x.1))?;
+
+            let step = TNumber::max(Abs::abs(delta.0), Abs::abs(delta.1));
+            trace(TraceRecord::without_bracket(Point::new(x.0, x.1), step));
+
+            let residual = system.residual(x);
+            if parameters.is_precise(step, residual) {
+                return Ok(ComputeRootResponse {
+                    root: Point::new(x.0, x.1),
+                    iterations: iteration,
+                    step,
+                    residual,
+                });
+            }
+        }
+
+        Err(MethodError::IterationLimitReached)
+    }
+}
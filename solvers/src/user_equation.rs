@@ -0,0 +1,225 @@
+//! Equation uploaded by host. Derivatives which host did not upload
+//! are approximated with central difference
+
+use protocol::expression::{Instruction, Program, ProgramTarget, MAX_STACK_DEPTH};
+use protocol::TNumber;
+
+use crate::{Exponent, Logarithm, Pow, Trigonometry};
+
+/// Name of the slot uploaded equation takes
+pub const NAME: &str = "uploaded equation";
+
+/// Step of central difference
+const STEP: TNumber = 1e-3;
+
+/// Function, first and second derivatives
+#[derive(Clone, Copy)]
+pub struct UserEquation {
+    programs: [Program; 3],
+}
+
+impl UserEquation {
+    pub const fn new() -> Self {
+        Self {
+            programs: [Program::empty(); 3],
+        }
+    }
+
+    /// New function replaces equation along with its derivatives
+    pub fn store(&mut self, target: ProgramTarget, program: Program) {
+        if target == ProgramTarget::Function {
+            *self = Self::new();
+        }
+
+        self.programs[target_index(target)] = program;
+    }
+
+    pub fn function(&self, x: TNumber) -> TNumber {
+        self.evaluate_target(ProgramTarget::Function, x)
+    }
+
+    pub fn first_derivative(&self, x: TNumber) -> TNumber {
+        if self.is_uploaded(ProgramTarget::FirstDerivative) {
+            return self.evaluate_target(ProgramTarget::FirstDerivative, x);
+        }
+
+        (self.function(x + STEP) - self.function(x - STEP)) / (2. * STEP)
+    }
+
+    pub fn second_derivative(&self, x: TNumber) -> TNumber {
+        if self.is_uploaded(ProgramTarget::SecondDerivative) {
+            return self.evaluate_target(ProgramTarget::SecondDerivative, x);
+        }
+
+        (self.first_derivative(x + STEP) - self.first_derivative(x - STEP)) / (2. * STEP)
+    }
+
+    fn is_uploaded(&self, target: ProgramTarget) -> bool {
+        !self.programs[target_index(target)].is_empty()
+    }
+
+    fn evaluate_target(&self, target: ProgramTarget, x: TNumber) -> TNumber {
+        evaluate(&self.programs[target_index(target)], x, 0.)
+    }
+}
+
+impl Default for UserEquation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn target_index(target: ProgramTarget) -> usize {
+    match target {
+        ProgramTarget::Function => 0,
+        ProgramTarget::FirstDerivative => 1,
+        ProgramTarget::SecondDerivative => 2,
+    }
+}
+
+/// `NaN` for empty program
+pub fn evaluate(program: &Program, x: TNumber, y: TNumber) -> TNumber {
+    let mut stack = [0. as TNumber; MAX_STACK_DEPTH];
+    let mut depth = 0;
+    for instruction in program.instructions() {
+        // program is validated, so stack neither underflows nor overflows
+        depth -= instruction.arity();
+        let operand = |index: usize| stack[depth + index];
+        let value = match instruction {
+            Instruction::Constant(value) => value,
+            Instruction::X => x,
+            Instruction::Y => y,
+            Instruction::Add => operand(0) + operand(1),
+            Instruction::Subtract => operand(0) - operand(1),
+            Instruction::Multiply => operand(0) * operand(1),
+            Instruction::Divide => operand(0) / operand(1),
+            Instruction::Pow => operand(0).pow(operand(1)),
+            Instruction::Negate => -operand(0),
+            Instruction::Sin => Trigonometry::sin(operand(0)),
+            Instruction::Cos => Trigonometry::cos(operand(0)),
+            Instruction::Ln => Logarithm::ln(operand(0)),
+            Instruction::Exp => Exponent::exp(operand(0)),
+        };
+        stack[depth] = value;
+        depth += 1;
+    }
+
+    match depth {
+        1 => stack[0],
+        _ => TNumber::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::expression::{Instruction, Program, ProgramTarget, MAX_PROGRAM_SIZE};
+    use protocol::TNumber;
+
+    use super::*;
+
+    /// Encoded programs are short, so they fit into a fixed buffer
+    fn program(instructions: &[Instruction]) -> Program {
+        let mut bytes = [0; MAX_PROGRAM_SIZE];
+        let mut length = 0;
+        Program::encode(instructions, |chunk| {
+            bytes[length..length + chunk.len()].copy_from_slice(chunk);
+            length += chunk.len();
+        });
+        Program::try_from_bytes(&bytes[..length]).unwrap()
+    }
+
+    fn assert_close(actual: TNumber, expected: TNumber) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn operands_keep_their_order() {
+        // (x - y) / 2
+        let program = program(&[
+            Instruction::X,
+            Instruction::Y,
+            Instruction::Subtract,
+            Instruction::Constant(2.),
+            Instruction::Divide,
+        ]);
+
+        assert_eq!(evaluate(&program, 7., 3.), 2.);
+    }
+
+    #[test]
+    fn power_raises_first_operand() {
+        let program = program(&[Instruction::X, Instruction::Constant(3.), Instruction::Pow]);
+
+        assert_close(evaluate(&program, 2., 0.), 8.);
+    }
+
+    #[test]
+    fn functions_are_applied() {
+        // -sin(x) + cos(x) * exp(ln(x))
+        let program = program(&[
+            Instruction::X,
+            Instruction::Sin,
+            Instruction::Negate,
+            Instruction::X,
+            Instruction::Cos,
+            Instruction::X,
+            Instruction::Ln,
+            Instruction::Exp,
+            Instruction::Multiply,
+            Instruction::Add,
+        ]);
+        let x: TNumber = 0.5;
+
+        assert_close(evaluate(&program, x, 0.), -x.sin() + x.cos() * x);
+    }
+
+    #[test]
+    fn empty_program_is_nan() {
+        assert!(evaluate(&Program::empty(), 1., 1.).is_nan());
+    }
+
+    #[test]
+    fn missing_derivatives_are_approximated() {
+        let mut equation = UserEquation::new();
+        // x^3
+        equation.store(
+            ProgramTarget::Function,
+            program(&[Instruction::X, Instruction::Constant(3.), Instruction::Pow]),
+        );
+
+        assert_close(equation.function(2.), 8.);
+        assert_close(equation.first_derivative(2.), 12.);
+        // nested difference loses most of f32 precision
+        assert!((equation.second_derivative(2.) - 12.).abs() < 1.);
+    }
+
+    #[test]
+    fn uploaded_derivative_is_used() {
+        let mut equation = UserEquation::new();
+        equation.store(ProgramTarget::Function, program(&[Instruction::X]));
+        // deliberately wrong, so it is told apart from the approximation
+        equation.store(
+            ProgramTarget::FirstDerivative,
+            program(&[Instruction::Constant(5.)]),
+        );
+
+        assert_eq!(equation.first_derivative(1.), 5.);
+    }
+
+    #[test]
+    fn new_function_drops_derivatives() {
+        let mut equation = UserEquation::new();
+        equation.store(
+            ProgramTarget::FirstDerivative,
+            program(&[Instruction::Constant(5.)]),
+        );
+        equation.store(ProgramTarget::Function, program(&[Instruction::X]));
+
+        assert_close(equation.first_derivative(1.), 1.);
+    }
+}
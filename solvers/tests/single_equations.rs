@@ -0,0 +1,341 @@
+use protocol::decode_error::DecodeError;
+use protocol::request::compute_method::Method;
+use protocol::request::payloads::{ComputeRootPayload, StoppingCriterion};
+use protocol::request::{EquationMode, EquationModeRaw, RequestPackage, Selection, SingleEquation};
+use protocol::response::MethodError;
+use protocol::TNumber;
+use solvers::{
+    check_roots_in_range, for_each_sign_change, is_bracket_valid, BisectionSolver, ChordSolver,
+    Equations, Exponent, GenericEquation, Logarithm, NewtonSolver, NonLinearEquation, Number,
+    SecantSolver, SimpleIterationSolver, Solver, SolverInput, Trigonometry, LEFT_BORDER,
+    POINT_INTERVAL_LENGTH, RIGHT_BORDER,
+};
+
+const EPSILON: TNumber = 1e-5;
+
+/// *x^2 - 2*, root is *sqrt(2)*
+const SQUARE_ROOT: NonLinearEquation = NonLinearEquation {
+    name: "x^2 - 2",
+    function: |x| x * x - 2.,
+    first_derivative: |x| 2. * x,
+    second_derivative: Some(|_| 2.),
+};
+
+/// *x^3 - x - 2*, single real root
+const CUBIC: NonLinearEquation = NonLinearEquation {
+    name: "x^3 - x - 2",
+    function: |x| x * x * x - x - 2.,
+    first_derivative: |x| 3. * x * x - 1.,
+    second_derivative: None,
+};
+
+/// *cos(x) - x*, root is Dottie number
+const DOTTIE: NonLinearEquation = NonLinearEquation {
+    name: "cos(x) - x",
+    function: |x| Trigonometry::cos(x) - x,
+    first_derivative: |x| -Trigonometry::sin(x) - 1.,
+    second_derivative: None,
+};
+
+/// Equation, bracket and the root within it
+const KNOWN_ROOTS: [(NonLinearEquation, TNumber, TNumber, TNumber); 3] = [
+    (SQUARE_ROOT, 1., 2., core::f32::consts::SQRT_2),
+    (CUBIC, 1., 2., 1.5213797),
+    (DOTTIE, 0., 1., 0.7390851),
+];
+
+fn input(start: TNumber, end: TNumber) -> SolverInput {
+    SolverInput {
+        start,
+        end,
+        epsilon: EPSILON,
+        max_iterations: 100,
+        criterion: StoppingCriterion::Both,
+    }
+}
+
+fn assert_finds_known_roots(solver: &dyn Solver<NonLinearEquation>) {
+    for (equation, start, end, root) in KNOWN_ROOTS {
+        let response = solver
+            .solve(&equation, &input(start, end))
+            .unwrap_or_else(|error| panic!("{}: {error:?}", equation.name));
+
+        assert!(
+            (response.root.x - root).abs() < 1e-4,
+            "{}: {} instead of {root}",
+            equation.name,
+            response.root.x
+        );
+        assert!(response.residual <= EPSILON, "{}", equation.name);
+        assert!(response.iterations >= 1, "{}", equation.name);
+    }
+}
+
+#[test]
+fn bisection_finds_known_roots() {
+    assert_finds_known_roots(&BisectionSolver);
+}
+
+#[test]
+fn chord_finds_known_roots() {
+    assert_finds_known_roots(&ChordSolver);
+}
+
+#[test]
+fn secant_finds_known_roots() {
+    assert_finds_known_roots(&SecantSolver);
+}
+
+#[test]
+fn newton_finds_known_roots() {
+    assert_finds_known_roots(&NewtonSolver);
+}
+
+#[test]
+fn simple_iteration_finds_known_roots() {
+    assert_finds_known_roots(&SimpleIterationSolver);
+}
+
+#[test]
+fn trace_has_record_per_iteration() {
+    let mut records = 0;
+    let response = NewtonSolver
+        .solve_traced(&CUBIC, &input(1., 2.), &mut |_| records += 1)
+        .unwrap();
+
+    assert_eq!(records, response.iterations);
+}
+
+#[test]
+fn first_step_of_bracketing_methods_is_bracket_width() {
+    let solvers: [&dyn Solver<NonLinearEquation>; 2] = [&BisectionSolver, &ChordSolver];
+    for solver in solvers {
+        let mut steps = Vec::new();
+        solver
+            .solve_traced(&CUBIC, &input(1., 2.), &mut |record| {
+                steps.push(record.step)
+            })
+            .unwrap();
+
+        assert_eq!(steps[0], 1.);
+        assert!(steps.iter().all(|step| step.is_finite()));
+    }
+}
+
+#[test]
+fn residual_criterion_ignores_step() {
+    let parameters = SolverInput {
+        criterion: StoppingCriterion::Residual,
+        epsilon: 1e-2,
+        ..input(1., 2.)
+    };
+    let response = BisectionSolver.solve(&SQUARE_ROOT, &parameters).unwrap();
+
+    assert!(response.residual <= 1e-2);
+    // interval is still wider than epsilon
+    assert!(response.iterations < 10);
+}
+
+#[test]
+fn iteration_limit_is_reported() {
+    let parameters = SolverInput {
+        max_iterations: 2,
+        ..input(1., 2.)
+    };
+
+    assert_eq!(
+        BisectionSolver.solve(&CUBIC, &parameters).unwrap_err(),
+        MethodError::IterationLimitReached
+    );
+}
+
+#[test]
+fn newton_fails_on_flat_tangent() {
+    // starts from the middle, where derivative is zero
+    let equation = NonLinearEquation {
+        function: |x| x * x - 1.,
+        first_derivative: |x| 2. * x,
+        second_derivative: None,
+        ..SQUARE_ROOT
+    };
+
+    assert_eq!(
+        NewtonSolver.solve(&equation, &input(-2., 2.)).unwrap_err(),
+        MethodError::ZeroDenominator
+    );
+}
+
+#[test]
+fn newton_fails_on_derivative_out_of_domain() {
+    let equation = NonLinearEquation {
+        first_derivative: |_| TNumber::NAN,
+        ..SQUARE_ROOT
+    };
+
+    assert_eq!(
+        NewtonSolver.solve(&equation, &input(0., 2.)).unwrap_err(),
+        MethodError::NotFinite
+    );
+}
+
+#[test]
+fn evaluation_out_of_domain_is_not_finite() {
+    let equation = NonLinearEquation {
+        function: Logarithm::ln,
+        first_derivative: |x| 1. / x,
+        second_derivative: None,
+        ..SQUARE_ROOT
+    };
+
+    assert_eq!(
+        BisectionSolver
+            .solve(&equation, &input(-2., -1.))
+            .unwrap_err(),
+        MethodError::NotFinite
+    );
+}
+
+#[test]
+fn range_without_roots_is_rejected() {
+    let equation = NonLinearEquation {
+        function: |x| x * x + 1.,
+        ..SQUARE_ROOT
+    };
+
+    assert!(matches!(
+        check_roots_in_range(&equation, &input(1., 2.)),
+        Err(MethodError::NoRootInRange { .. })
+    ));
+}
+
+#[test]
+fn range_with_two_roots_is_rejected_with_suggestion() {
+    let equation = NonLinearEquation {
+        function: |x| x * x - 0.25,
+        ..SQUARE_ROOT
+    };
+
+    let Err(error) = check_roots_in_range(&equation, &input(-1., 1.)) else {
+        panic!("two roots are accepted");
+    };
+    assert!(matches!(error, MethodError::MoreThanOneRootInRange { .. }));
+
+    let suggestion = error.suggestion().expect("suggestion is given");
+    let roots_within = [-0.5, 0.5]
+        .into_iter()
+        .filter(|root| suggestion.left <= *root && *root <= suggestion.right)
+        .count();
+    assert_eq!(roots_within, 1);
+}
+
+#[test]
+fn isolated_root_is_accepted() {
+    assert_eq!(check_roots_in_range(&CUBIC, &input(1., 2.)), Ok(()));
+}
+
+#[test]
+fn bracket_has_to_be_in_order() {
+    assert!(is_bracket_valid(-1., 1.));
+    assert!(!is_bracket_valid(1., -1.));
+    assert!(!is_bracket_valid(1., 1.));
+    assert!(!is_bracket_valid(LEFT_BORDER - 1., 1.));
+    assert!(!is_bracket_valid(-1., RIGHT_BORDER));
+}
+
+#[test]
+fn every_sign_change_is_found() {
+    let equation = NonLinearEquation {
+        function: |x| (x - 0.5) * (x + 2.3) * (x - 7.1),
+        ..SQUARE_ROOT
+    };
+
+    let mut brackets = Vec::new();
+    for_each_sign_change(&equation, |left, right| brackets.push((left, right)));
+
+    assert_eq!(brackets.len(), 3);
+    for ((left, right), root) in brackets.into_iter().zip([-2.3, 0.5, 7.1]) {
+        assert!(left <= root && root <= right);
+    }
+}
+
+#[test]
+fn sign_change_next_to_right_border_is_found() {
+    let equation = NonLinearEquation {
+        function: |x| x - 9.99,
+        ..SQUARE_ROOT
+    };
+
+    let mut brackets = Vec::new();
+    for_each_sign_change(&equation, |left, right| brackets.push((left, right)));
+
+    assert_eq!(
+        brackets,
+        [(RIGHT_BORDER - POINT_INTERVAL_LENGTH, RIGHT_BORDER)]
+    );
+}
+
+struct SineOfSquare;
+
+impl GenericEquation for SineOfSquare {
+    const NAME: &'static str = "sin(x^2) / x + ln(x) * exp(x)";
+
+    fn function<T: Number>(x: T) -> T {
+        (x * x).sin() / x + x.ln() * x.exp()
+    }
+}
+
+#[test]
+fn dual_numbers_give_derivative() {
+    let equation = NonLinearEquation::generic::<SineOfSquare>();
+    let expected = |x: TNumber| {
+        2. * Trigonometry::cos(x * x) - Trigonometry::sin(x * x) / (x * x)
+            + Exponent::exp(x) / x
+            + Logarithm::ln(x) * Exponent::exp(x)
+    };
+
+    assert_eq!(equation.name, SineOfSquare::NAME);
+    for x in [0.5, 1., 2.5] {
+        let derivative = (equation.first_derivative)(x);
+        assert!(
+            (derivative - expected(x)).abs() < 1e-4 * expected(x).abs().max(1.),
+            "{derivative} instead of {} at {x}",
+            expected(x)
+        );
+    }
+}
+
+#[test]
+fn unknown_equation_is_rejected_before_indexing() {
+    static SINGLE: [NonLinearEquation; 1] = [SQUARE_ROOT];
+    let equations = Equations {
+        single: &SINGLE,
+        systems: &[],
+    };
+    let compute = |equation_number| RequestPackage::ComputeRoot {
+        payload: ComputeRootPayload {
+            epsilon: EPSILON,
+            mode: EquationMode::Single(SingleEquation {
+                method: Method::Newton,
+                equation_number,
+            }),
+            max_iterations: 100,
+            criterion: StoppingCriterion::Step,
+        },
+    };
+    let points = RequestPackage::FunctionPoints {
+        payload: Selection {
+            mode: EquationModeRaw::SystemOfEquations,
+            index: 0,
+        },
+    };
+
+    assert_eq!(equations.check_request(&compute(0)), Ok(()));
+    assert_eq!(
+        equations.check_request(&compute(1)),
+        Err(DecodeError::UnknownEquation(1))
+    );
+    assert_eq!(
+        equations.check_request(&points),
+        Err(DecodeError::UnknownSystem(0))
+    );
+}
@@ -0,0 +1,86 @@
+use protocol::request::payloads::StoppingCriterion;
+use protocol::response::MethodError;
+use protocol::TNumber;
+use solvers::builtin::SINE_AND_COSINE;
+use solvers::system_of_equations::{
+    EquationWithPhi, NewtonSolverForSystems, SimpleIteratorSolverForSystems, SystemOfEquations,
+};
+use solvers::{Solver, SolverInput};
+
+const EPSILON: TNumber = 1e-5;
+
+/// Intersection of both curves of the system arduino is flashed with
+const ROOT: (TNumber, TNumber) = (-0.2898, 1.1429);
+
+fn input(start: TNumber, end: TNumber) -> SolverInput {
+    SolverInput {
+        start,
+        end,
+        epsilon: EPSILON,
+        max_iterations: 100,
+        criterion: StoppingCriterion::Both,
+    }
+}
+
+fn assert_finds_root(solver: &dyn Solver<SystemOfEquations>) {
+    let response = solver.solve(&SINE_AND_COSINE, &input(0., 1.)).unwrap();
+
+    assert!(
+        (response.root.x - ROOT.0).abs() < 1e-3,
+        "{:?}",
+        response.root
+    );
+    assert!(
+        (response.root.y - ROOT.1).abs() < 1e-3,
+        "{:?}",
+        response.root
+    );
+    assert!(response.residual <= EPSILON);
+}
+
+#[test]
+fn newton_finds_intersection() {
+    assert_finds_root(&NewtonSolverForSystems);
+}
+
+#[test]
+fn simple_iteration_finds_intersection() {
+    assert_finds_root(&SimpleIteratorSolverForSystems);
+}
+
+#[test]
+fn simple_iteration_checks_convergence() {
+    // phi grows faster than its argument
+    let equation = EquationWithPhi {
+        phi: |(x, y)| 2. * (x + y),
+        phi_gradient: |_| (2., 2.),
+        ..SINE_AND_COSINE.first
+    };
+    let system = SystemOfEquations {
+        first: equation.clone(),
+        second: equation,
+    };
+
+    assert_eq!(
+        SimpleIteratorSolverForSystems
+            .solve(&system, &input(0., 1.))
+            .unwrap_err(),
+        MethodError::ConvergenceConditionNotMet
+    );
+}
+
+#[test]
+fn newton_fails_on_singular_jacobian() {
+    // both equations have the same gradient
+    let system = SystemOfEquations {
+        first: SINE_AND_COSINE.first,
+        second: SINE_AND_COSINE.first,
+    };
+
+    assert_eq!(
+        NewtonSolverForSystems
+            .solve(&system, &input(0., 1.))
+            .unwrap_err(),
+        MethodError::ZeroDenominator
+    );
+}